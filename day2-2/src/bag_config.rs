@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+
+use crate::Bag;
+
+pub const BAG_FILE_VAR: &str = "AOC_BAG_FILE";
+pub const RED_VAR: &str = "AOC_BAG_RED";
+pub const GREEN_VAR: &str = "AOC_BAG_GREEN";
+pub const BLUE_VAR: &str = "AOC_BAG_BLUE";
+
+// The cube counts gathered so far. Later sources override earlier ones, in the order:
+// bag file, environment variables, command line arguments.
+#[derive(Default)]
+struct PartialBag {
+    num_red: Option<i32>,
+    num_green: Option<i32>,
+    num_blue: Option<i32>,
+}
+
+// Format of a bag preset file, e.g. in TOML:
+//   red = 12
+//   green = 13
+//   blue = 14
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BagFile {
+    red: Option<i64>,
+    green: Option<i64>,
+    blue: Option<i64>,
+}
+
+// Builds the bag from the arguments given for it, falling back to the environment
+// (looked up through get_var) and an optional bag file for any counts not given on the command line.
+pub fn load_bag(args: &[String], get_var: impl Fn(&str) -> Option<String>) -> Result<Bag, String> {
+    let mut bag_file: Option<String> = get_var(BAG_FILE_VAR);
+    let mut from_args: PartialBag = PartialBag::default();
+
+    if args.len() == 3 && args.iter().all(|arg| !arg.starts_with("--")) {
+        // Positional form: num_red num_green num_blue
+        from_args.num_red = Some(parse_count("num_red", &args[0])?);
+        from_args.num_green = Some(parse_count("num_green", &args[1])?);
+        from_args.num_blue = Some(parse_count("num_blue", &args[2])?);
+    } else {
+        // Named form: --red N --green N --blue N --bag-file path
+        let mut i: usize = 0;
+        while i < args.len() {
+            let flag: &String = &args[i];
+            let value: &String = match args.get(i + 1) {
+                Some(value) => value,
+                None => {
+                    return Err(format!("Missing value for {}", flag));
+                },
+            };
+            match flag.as_str() {
+                "--red" => from_args.num_red = Some(parse_count("num_red", value)?),
+                "--green" => from_args.num_green = Some(parse_count("num_green", value)?),
+                "--blue" => from_args.num_blue = Some(parse_count("num_blue", value)?),
+                "--bag-file" => bag_file = Some(value.clone()),
+                _ => {
+                    return Err(format!("Unexpected argument {}", flag));
+                },
+            };
+            i += 2;
+        }
+    }
+
+    let mut bag: PartialBag = match bag_file {
+        Some(path) => read_bag_file(&path)?,
+        None => PartialBag::default(),
+    };
+
+    let from_env: PartialBag = PartialBag {
+        num_red: parse_env_count(&get_var, RED_VAR)?,
+        num_green: parse_env_count(&get_var, GREEN_VAR)?,
+        num_blue: parse_env_count(&get_var, BLUE_VAR)?,
+    };
+
+    for overrides in [from_env, from_args] {
+        bag.num_red = overrides.num_red.or(bag.num_red);
+        bag.num_green = overrides.num_green.or(bag.num_green);
+        bag.num_blue = overrides.num_blue.or(bag.num_blue);
+    }
+
+    return Ok(Bag::new(
+        bag.num_red.ok_or("No value given for num_red".to_string())?,
+        bag.num_green.ok_or("No value given for num_green".to_string())?,
+        bag.num_blue.ok_or("No value given for num_blue".to_string())?,
+    ));
+}
+
+fn read_bag_file(path: &String) -> Result<PartialBag, String> {
+    let contents: String = match fs::read_to_string(path) {
+        Err(e) => {
+            return Err(format!("Error reading bag file {}: {}", path, e));
+        },
+        Ok(f) => f,
+    };
+
+    // Anything that isn't explicitly JSON is treated as TOML
+    let is_json: bool = Path::new(path).extension().is_some_and(|extension| extension == "json");
+    let bag_file: BagFile = if is_json {
+        serde_json::from_str(&contents).map_err(|e| format!("Error parsing bag file {}: {}", path, e))?
+    } else {
+        toml::from_str(&contents).map_err(|e| format!("Error parsing bag file {}: {}", path, e))?
+    };
+
+    return Ok(PartialBag {
+        num_red: bag_file.red.map(|count| validate_count("red", count)).transpose()?,
+        num_green: bag_file.green.map(|count| validate_count("green", count)).transpose()?,
+        num_blue: bag_file.blue.map(|count| validate_count("blue", count)).transpose()?,
+    });
+}
+
+fn parse_env_count(get_var: &impl Fn(&str) -> Option<String>, name: &str) -> Result<Option<i32>, String> {
+    return match get_var(name) {
+        Some(value) => Ok(Some(parse_count(name, &value)?)),
+        None => Ok(None),
+    };
+}
+
+// A single cube count, which must fit in an i32 and not be negative
+pub fn parse_count(name: &str, value: &str) -> Result<i32, String> {
+    let count: i64 = match value.trim().parse::<i64>() {
+        Err(e) => {
+            return Err(format!("Error parsing {} {}: {}", name, value, e));
+        },
+        Ok(result) => result,
+    };
+    return validate_count(name, count);
+}
+
+fn validate_count(name: &str, count: i64) -> Result<i32, String> {
+    if count < 0 {
+        return Err(format!("{} must not be negative, got {}", name, count));
+    }
+    return match i32::try_from(count) {
+        Err(_) => Err(format!("{} is too large, got {}", name, count)),
+        Ok(result) => Ok(result),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::collections::HashMap;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        return values.iter().map(|value| value.to_string()).collect();
+    }
+
+    #[test]
+    fn test_sources() {
+        let no_env = |_: &str| None;
+        let bag: Bag = load_bag(&args(&["12", "13", "14"]), no_env).unwrap();
+        assert_eq!((bag.num_red(), bag.num_green(), bag.num_blue()), (12, 13, 14));
+
+        let env: HashMap<&str, &str> = HashMap::from([(RED_VAR, "1"), (GREEN_VAR, "2"), (BLUE_VAR, "3")]);
+        let get_var = |name: &str| env.get(name).map(|value| value.to_string());
+        let bag: Bag = load_bag(&args(&["--green", "20"]), get_var).unwrap();
+        assert_eq!((bag.num_red(), bag.num_green(), bag.num_blue()), (1, 20, 3));
+
+        let path = std::env::temp_dir().join(format!("day2-2-bag-{}.json", std::process::id()));
+        fs::write(&path, "{\"red\": 12, \"green\": 13, \"blue\": 14}").unwrap();
+        let bag: Bag = load_bag(&args(&["--bag-file", path.to_str().unwrap(), "--blue", "5"]), no_env).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!((bag.num_red(), bag.num_green(), bag.num_blue()), (12, 13, 5));
+    }
+
+    #[test]
+    fn test_invalid_counts() {
+        let no_env = |_: &str| None;
+        assert!(load_bag(&args(&["12", "-13", "14"]), no_env).is_err());
+        assert!(load_bag(&args(&["--red", "12", "--green", "13"]), no_env).is_err());
+        assert!(load_bag(&args(&["--red", "12", "--green", "13", "--blue"]), no_env).is_err());
+        assert!(load_bag(&args(&["--purple", "1"]), no_env).is_err());
+
+        let path = std::env::temp_dir().join(format!("day2-2-bag-{}.toml", std::process::id()));
+        fs::write(&path, "red = 12\ngreen = -13\nblue = 14\n").unwrap();
+        let result = load_bag(&args(&["--bag-file", path.to_str().unwrap()]), no_env);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
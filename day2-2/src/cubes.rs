@@ -0,0 +1,256 @@
+use std::cmp;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+use regex::Regex;
+use regex::Captures;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+// The cubes held in a bag
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bag {
+    #[cfg_attr(feature = "serde", serde(rename = "red"))]
+    pub(crate) num_red: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "green"))]
+    pub(crate) num_green: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "blue"))]
+    pub(crate) num_blue: i32,
+}
+
+// The cubes revealed in one handful from a bag; a given Hand is a subset of the contents of the Bag
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hand {
+    #[cfg_attr(feature = "serde", serde(rename = "red"))]
+    pub(crate) num_red: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "green"))]
+    pub(crate) num_green: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "blue"))]
+    pub(crate) num_blue: i32,
+}
+
+// Bags and hands share the same cube-count operations, which only differ in the type they produce.
+// Comparisons are by component-wise dominance, so e.g. two bags where each has more of some colour
+// than the other are incomparable.
+macro_rules! impl_cube_counts {
+    ($name:ident) => {
+        impl $name {
+            pub fn new(num_red: i32, num_green: i32, num_blue: i32) -> $name {
+                return $name {
+                    num_red,
+                    num_green,
+                    num_blue,
+                };
+            }
+
+            pub fn num_red(&self) -> i32 {
+                return self.num_red;
+            }
+
+            pub fn num_green(&self) -> i32 {
+                return self.num_green;
+            }
+
+            pub fn num_blue(&self) -> i32 {
+                return self.num_blue;
+            }
+
+            // Component-wise maximum: the smallest counts containing both
+            pub fn join(&self, other: &$name) -> $name {
+                return $name {
+                    num_red: cmp::max(self.num_red, other.num_red),
+                    num_green: cmp::max(self.num_green, other.num_green),
+                    num_blue: cmp::max(self.num_blue, other.num_blue),
+                };
+            }
+
+            // Component-wise minimum: the largest counts contained in both
+            pub fn meet(&self, other: &$name) -> $name {
+                return $name {
+                    num_red: cmp::min(self.num_red, other.num_red),
+                    num_green: cmp::min(self.num_green, other.num_green),
+                    num_blue: cmp::min(self.num_blue, other.num_blue),
+                };
+            }
+
+            // Computed in i64, as three i32 counts easily overflow an i32. None if it overflows an i64 as well.
+            pub fn power(&self) -> Option<i64> {
+                return (self.num_red as i64).checked_mul(self.num_green as i64)?.checked_mul(self.num_blue as i64);
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
+                return compare_counts(
+                    (self.num_red, self.num_green, self.num_blue),
+                    (other.num_red, other.num_green, other.num_blue),
+                );
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                return $name {
+                    num_red: self.num_red + other.num_red,
+                    num_green: self.num_green + other.num_green,
+                    num_blue: self.num_blue + other.num_blue,
+                };
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                return write!(f, "{} red, {} green, {} blue", self.num_red, self.num_green, self.num_blue);
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(description: &str) -> Result<$name, String> {
+                let (num_red, num_green, num_blue) = parse_cube_counts(description)?;
+                return Ok($name {
+                    num_red,
+                    num_green,
+                    num_blue,
+                });
+            }
+        }
+    };
+}
+
+impl_cube_counts!(Bag);
+impl_cube_counts!(Hand);
+
+// The smallest bag a hand could have been drawn from
+impl From<Hand> for Bag {
+    fn from(hand: Hand) -> Bag {
+        return Bag {
+            num_red: hand.num_red,
+            num_green: hand.num_green,
+            num_blue: hand.num_blue,
+        };
+    }
+}
+
+// A hand compares against a bag by the same dominance order, so `hand <= bag` means the hand could come from the bag
+impl PartialEq<Bag> for Hand {
+    fn eq(&self, other: &Bag) -> bool {
+        return Bag::from(*self) == *other;
+    }
+}
+
+impl PartialOrd<Bag> for Hand {
+    fn partial_cmp(&self, other: &Bag) -> Option<Ordering> {
+        return Bag::from(*self).partial_cmp(other);
+    }
+}
+
+fn compare_counts(lhs: (i32, i32, i32), rhs: (i32, i32, i32)) -> Option<Ordering> {
+    let orderings: [Ordering; 3] = [lhs.0.cmp(&rhs.0), lhs.1.cmp(&rhs.1), lhs.2.cmp(&rhs.2)];
+    if orderings.iter().all(|ordering| *ordering != Ordering::Greater) {
+        if orderings.iter().all(|ordering| *ordering == Ordering::Equal) {
+            return Some(Ordering::Equal);
+        }
+        return Some(Ordering::Less);
+    }
+    if orderings.iter().all(|ordering| *ordering != Ordering::Less) {
+        return Some(Ordering::Greater);
+    }
+    return None;
+}
+
+// Format: "X red, Y green, Z blue" in any order, with any colour optionally omitted
+fn parse_cube_counts(description: &str) -> Result<(i32, i32, i32), String> {
+    let mut num_red: Option<i32> = None;
+    let mut num_green: Option<i32> = None;
+    let mut num_blue: Option<i32> = None;
+
+    let colour_description_regex: Regex = Regex::new(r"^([0-9]+) (red|green|blue)$").unwrap();
+
+    for colour_description in description.split(",") {
+        let trimmed = colour_description.trim();
+        // Check it's at all valid
+        let caps: Captures = match colour_description_regex.captures(trimmed) {
+            Some(caps) => caps,
+            None => {
+                return Err(format!("Malformed hand description {}", description));
+            },
+        };
+        // The regex only checks for digits, so the count can still be too large
+        let num_cubes: i32 = match caps[1].parse::<i32>() {
+            Err(e) => {
+                return Err(format!("Error parsing cube count {} for hand {}: {}", &caps[1], description, e));
+            },
+            Ok(result) => result,
+        };
+        if trimmed.ends_with("red") {
+            if num_red.is_none() {
+                num_red = Some(num_cubes);
+            } else {
+                return Err(format!("Multiple instances of red for hand {}", description));
+            }
+        }
+        if trimmed.ends_with("green") {
+            if num_green.is_none() {
+                num_green = Some(num_cubes);
+            } else {
+                return Err(format!("Multiple instances of green for hand {}", description));
+            }
+        }
+        if trimmed.ends_with("blue") {
+            if num_blue.is_none() {
+                num_blue = Some(num_cubes);
+            } else {
+                return Err(format!("Multiple instances of blue for hand {}", description));
+            }
+        }
+    }
+
+    return Ok((num_red.unwrap_or(0), num_green.unwrap_or(0), num_blue.unwrap_or(0)));
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lattice() {
+        let a: Bag = Bag::new(1, 5, 3);
+        let b: Bag = Bag::new(4, 2, 3);
+
+        assert_eq!(a.partial_cmp(&b), None);
+        assert!(a.meet(&b) <= a && a.meet(&b) <= b);
+        assert!(a <= a.join(&b) && b <= a.join(&b));
+        assert_eq!(a.join(&b), Bag::new(4, 5, 3));
+        assert_eq!(a.meet(&b), Bag::new(1, 2, 3));
+        assert_eq!(a + b, Bag::new(5, 7, 6));
+        assert_eq!(a.join(&b).power(), Some(60));
+        assert_eq!(Bag::new(2000, 2000, 2000).power(), Some(8_000_000_000));
+        assert_eq!(Bag::new(i32::MAX, i32::MAX, i32::MAX).power(), None);
+
+        let hand: Hand = Hand::new(1, 2, 3);
+        assert!(hand <= a && hand <= b);
+        assert_eq!(Hand::new(0, 6, 0).partial_cmp(&a), None);
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let hand: Hand = " 3 blue, 4 red".parse().unwrap();
+        assert_eq!(hand, Hand::new(4, 0, 3));
+        assert_eq!(hand.to_string(), "4 red, 0 green, 3 blue");
+        assert_eq!(hand.to_string().parse::<Hand>().unwrap(), hand);
+        assert_eq!("12 red, 13 green, 14 blue".parse::<Bag>().unwrap(), Bag::new(12, 13, 14));
+        assert!("1 red, 2 red".parse::<Hand>().is_err());
+        assert!("99999999999 red".parse::<Hand>().is_err());
+        assert!("x5 redish".parse::<Bag>().is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::parse_games;
+use crate::Game;
+use crate::Hand;
+
+// Human-readable names of every supported format, for usage and error messages
+pub const FORMAT_NAMES: &str = "text, csv, json or jsonl";
+
+// The first line of every CSV file, which parse_games_csv requires
+const CSV_HEADER: &str = "game_id,red,green,blue";
+
+// The ways a list of games can be written out and read back in
+pub enum GameFormat {
+    // The puzzle input format, one "Game X: ..." per line
+    Text,
+    // One "game_id,red,green,blue" row per hand, with consecutive rows of the same game_id forming one game
+    Csv,
+    // A single JSON array of games (requires the serde feature)
+    Json,
+    // One JSON game per line (requires the serde feature)
+    JsonLines,
+}
+
+pub fn format_for_name(name: &str) -> Option<GameFormat> {
+    return match name {
+        "text" => Some(GameFormat::Text),
+        "csv" => Some(GameFormat::Csv),
+        "json" => Some(GameFormat::Json),
+        "jsonl" => Some(GameFormat::JsonLines),
+        _ => None,
+    };
+}
+
+// Picks the format from the file extension, falling back to the puzzle input format
+pub fn format_for_path(path: &str) -> GameFormat {
+    let extension: &str = match path.rsplit_once(".") {
+        Some((_, extension)) => extension,
+        None => "",
+    };
+    return format_for_name(extension).unwrap_or(GameFormat::Text);
+}
+
+pub fn parse_games_as(description: &String, format: &GameFormat) -> Result<Vec<Game>, String> {
+    return match format {
+        GameFormat::Text => parse_games(description),
+        GameFormat::Csv => parse_games_csv(description),
+        GameFormat::Json => parse_games_json(description),
+        GameFormat::JsonLines => parse_games_json_lines(description),
+    };
+}
+
+pub fn write_games_as(games: &Vec<Game>, format: &GameFormat) -> Result<String, String> {
+    return match format {
+        GameFormat::Text => Ok(write_games_text(games)),
+        GameFormat::Csv => Ok(write_games_csv(games)),
+        GameFormat::Json => write_games_json(games),
+        GameFormat::JsonLines => write_games_json_lines(games),
+    };
+}
+
+fn write_games_text(games: &Vec<Game>) -> String {
+    let mut output: String = String::new();
+    for game in games {
+        output += &format!("{}\n", game);
+    }
+    return output;
+}
+
+fn write_games_csv(games: &Vec<Game>) -> String {
+    let mut output: String = format!("{}\n", CSV_HEADER);
+    for game in games {
+        for hand in game.hands() {
+            output += &format!("{},{},{},{}\n", game.id(), hand.num_red(), hand.num_green(), hand.num_blue());
+        }
+    }
+    return output;
+}
+
+fn parse_games_csv(description: &String) -> Result<Vec<Game>, String> {
+    let mut games: Vec<Game> = Vec::new();
+    let mut current_id: Option<i32> = None;
+    let mut current_hands: Vec<Hand> = Vec::new();
+
+    // Without a header, the first row would otherwise be mistaken for one and silently dropped
+    let header: &str = description.split("\n").next().unwrap_or("").trim();
+    if header != CSV_HEADER {
+        return Err(format!("Expected CSV header {} on line 1, found {}", CSV_HEADER, header));
+    }
+
+    for (line_number, line) in description.split("\n").enumerate().skip(1) {
+        let line: &str = line.trim();
+        // Skip empty lines
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut values: Vec<i32> = Vec::new();
+        for value in line.split(",") {
+            match value.trim().parse::<i32>() {
+                Err(e) => {
+                    return Err(format!("Error parsing row {} on line {}: {}", line, line_number + 1, e));
+                },
+                Ok(result) => values.push(result),
+            };
+        }
+        if values.len() != 4 {
+            return Err(format!("Unexpected number of columns on line {}\nExpected: 4, Actual: {}", line_number + 1, values.len()));
+        }
+
+        // A new game_id finishes the previous game
+        if let Some(id) = current_id {
+            if id != values[0] {
+                games.push(Game::new(id, current_hands));
+                current_hands = Vec::new();
+            }
+        }
+        current_id = Some(values[0]);
+        current_hands.push(Hand::new(values[1], values[2], values[3]));
+    }
+
+    if let Some(id) = current_id {
+        games.push(Game::new(id, current_hands));
+    }
+
+    return Ok(games);
+}
+
+#[cfg(feature = "serde")]
+fn write_games_json(games: &Vec<Game>) -> Result<String, String> {
+    return match serde_json::to_string_pretty(games) {
+        Err(e) => Err(format!("Error writing games as JSON: {}", e)),
+        Ok(result) => Ok(result + "\n"),
+    };
+}
+
+#[cfg(feature = "serde")]
+fn parse_games_json(description: &String) -> Result<Vec<Game>, String> {
+    return serde_json::from_str(description).map_err(|e| format!("Error parsing games as JSON: {}", e));
+}
+
+#[cfg(feature = "serde")]
+fn write_games_json_lines(games: &Vec<Game>) -> Result<String, String> {
+    let mut output: String = String::new();
+    for game in games {
+        match serde_json::to_string(game) {
+            Err(e) => {
+                return Err(format!("Error writing game {} as JSON: {}", game.id(), e));
+            },
+            Ok(result) => output += &format!("{}\n", result),
+        };
+    }
+    return Ok(output);
+}
+
+#[cfg(feature = "serde")]
+fn parse_games_json_lines(description: &String) -> Result<Vec<Game>, String> {
+    let mut games: Vec<Game> = Vec::new();
+    for (line_number, line) in description.split("\n").enumerate() {
+        // Skip empty lines
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Err(e) => {
+                return Err(format!("Error parsing game on line {}: {}", line_number + 1, e));
+            },
+            Ok(game) => games.push(game),
+        };
+    }
+    return Ok(games);
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_games_json(_games: &Vec<Game>) -> Result<String, String> {
+    return Err("JSON output requires the serde feature".to_string());
+}
+
+#[cfg(not(feature = "serde"))]
+fn parse_games_json(_description: &String) -> Result<Vec<Game>, String> {
+    return Err("JSON input requires the serde feature".to_string());
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_games_json_lines(_games: &Vec<Game>) -> Result<String, String> {
+    return Err("JSON output requires the serde feature".to_string());
+}
+
+#[cfg(not(feature = "serde"))]
+fn parse_games_json_lines(_description: &String) -> Result<Vec<Game>, String> {
+    return Err("JSON input requires the serde feature".to_string());
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::TEST_GAMES;
+
+    fn round_trip(format: &GameFormat) {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+        let written: String = write_games_as(&games, format).unwrap();
+        assert_eq!(parse_games_as(&written, format).unwrap(), games);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(&GameFormat::Text);
+        round_trip(&GameFormat::Csv);
+    }
+
+    #[test]
+    fn test_csv_header() {
+        let games: Vec<Game> = parse_games_as(&"game_id,red,green,blue\n1,4,0,3\n".to_string(), &GameFormat::Csv).unwrap();
+        assert_eq!(games, vec![Game::new(1, vec![Hand::new(4, 0, 3)])]);
+
+        assert!(parse_games_as(&"1,4,0,3\n1,1,2,6\n".to_string(), &GameFormat::Csv).is_err());
+        assert!(parse_games_as(&"id,red,green,blue\n1,4,0,3\n".to_string(), &GameFormat::Csv).is_err());
+        assert!(parse_games_as(&String::new(), &GameFormat::Csv).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_round_trip_json() {
+        round_trip(&GameFormat::Json);
+        round_trip(&GameFormat::JsonLines);
+
+        let line: &str = "{\"id\": 7, \"hands\": [{\"red\": 1, \"green\": 2, \"blue\": 3}]}";
+        let games: Vec<Game> = parse_games_as(&line.to_string(), &GameFormat::JsonLines).unwrap();
+        assert_eq!(games, vec![Game::new(7, vec![Hand::new(1, 2, 3)])]);
+    }
+}
@@ -0,0 +1,353 @@
+use std::cmp;
+use std::fmt;
+use regex::Regex;
+use regex::Captures;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+pub mod bag_config;
+pub mod cubes;
+pub mod formats;
+pub mod repl;
+pub mod report;
+pub mod validation;
+
+pub use cubes::Bag;
+pub use cubes::Hand;
+
+pub fn sum_ids_of_possible_games(games: &Vec<Game>, bag: &Bag) -> i32 {
+    let mut sum_of_possible_game_ids: i32 = 0;
+    for game in games {
+        if is_game_possible(game, bag) {
+            sum_of_possible_game_ids += game.id;
+        }
+    }
+    return sum_of_possible_game_ids;
+}
+
+pub fn sum_powers_of_smallest_bags(games: &Vec<Game>) -> Result<i64, String> {
+    let mut sum_of_smallest_game_powers: i64 = 0;
+    for game in games {
+        let power: i64 = smallest_bag_power(game)?;
+        sum_of_smallest_game_powers = match sum_of_smallest_game_powers.checked_add(power) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of powers overflowed".to_string());
+            },
+        };
+    }
+    return Ok(sum_of_smallest_game_powers);
+}
+
+pub fn smallest_bag_power(game: &Game) -> Result<i64, String> {
+    return match find_smallest_bag_for_game(game).power() {
+        Some(power) => Ok(power),
+        None => Err(format!("Power of the smallest bag for game {} overflowed", game.id)),
+    };
+}
+
+pub fn parse_games(description: &String) -> Result<Vec<Game>, String> {
+    let mut games: Vec<Game> = Vec::new();
+    for line in description.split("\n") {
+        // Skip empty lines
+        if line.is_empty() {
+            continue;
+        }
+        // Parse games
+        match parse_game(&line.to_string()) {
+            Err(e) => {
+                return Err(format!("Error parsing game {}: {}", line, e));
+            },
+            Ok(g) => games.push(g),
+        };
+    }
+    return Ok(games);
+}
+
+pub fn parse_game(description: &String) -> Result<Game, String> {
+    let game_parts: Vec<&str> = description.split(":").collect();
+    if game_parts.len() != 2 {
+        return Err(format!("Unexpected number of parts for game {}\nExpected: 2, Actual: {}", description, game_parts.len()));
+    }
+
+    let game_header: String = game_parts[0].to_string();
+    let game_hands: String = game_parts[1].to_string();
+
+    let id = match parse_game_header(&game_header) {
+        Err(e) => {
+            return Err(e);
+        },
+        Ok(result) => result,
+    };
+
+    let mut hands: Vec<Hand> = Vec::new();
+    for game_hand_string in game_hands.split(";") {
+        match game_hand_string.parse::<Hand>() {
+            Err(e) => {
+                return Err(e);
+            },
+            Ok(result) => {
+                hands.push(result);
+            },
+        };
+    }
+
+    return Ok(Game {
+        id: id,
+        hands: hands
+    });
+}
+
+fn parse_game_header(description: &String) -> Result<i32, String> {
+    // Format: "Game X" where X is the id of the game
+    let game_header_regex: Regex = Regex::new(r"Game ([0-9]+)").unwrap();
+    let caps: Captures = match game_header_regex.captures(description) {
+        Some(caps) => caps,
+        None => {
+            return Err("Malformed game header.".to_string());
+        },
+    };
+    // We know it's a valid integer as it matches the regex
+    return Ok(caps[1].parse::<i32>().unwrap());
+}
+
+pub fn find_smallest_bag_for_game(game: &Game) -> Bag {
+    let mut bag: Bag = Bag::default();
+
+    for hand in &game.hands {
+        bag = bag.join(&Bag::from(*hand));
+    }
+
+    return bag;
+}
+
+pub fn is_game_possible(game: &Game, bag: &Bag) -> bool {
+    for hand in &game.hands {
+        if !is_hand_possible(&hand, &bag) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+pub fn is_hand_possible(hand: &Hand, bag: &Bag) -> bool {
+    return hand <= bag;
+}
+
+pub fn find_cheapest_bag_for_games(
+    games: &Vec<Game>,
+    min_games: usize,
+    required_game_ids: &Vec<i32>,
+    cost: &BagCost,
+) -> Result<BagSelection, String> {
+    // A bag makes a game possible iff it contains the smallest bag for that game,
+    // so only the smallest bags matter from here on.
+    let smallest_bags: Vec<Bag> = games.iter().map(find_smallest_bag_for_game).collect();
+
+    if min_games > games.len() {
+        return Err(format!("Cannot make {} games possible, only {} games available", min_games, games.len()));
+    }
+
+    // Any acceptable bag must contain the smallest bag for each required game
+    let mut floor: Bag = Bag::default();
+    for required_game_id in required_game_ids {
+        let index: usize = match games.iter().position(|game| game.id == *required_game_id) {
+            Some(index) => index,
+            None => {
+                return Err(format!("Required game {} not found", required_game_id));
+            },
+        };
+        floor = floor.join(&smallest_bags[index]);
+    }
+
+    // The cheapest bag only ever needs as many cubes of a colour as some game needs,
+    // so try each candidate red and green count and take the fewest blue cubes that admit enough games.
+    let red_candidates: Vec<i32> = candidate_counts(floor.num_red, smallest_bags.iter().map(|bag| bag.num_red));
+    let green_candidates: Vec<i32> = candidate_counts(floor.num_green, smallest_bags.iter().map(|bag| bag.num_green));
+
+    let mut cheapest: Option<(i64, Bag)> = None;
+    for num_red in &red_candidates {
+        for num_green in &green_candidates {
+            let mut admitted_blues: Vec<i32> = smallest_bags
+                .iter()
+                .filter(|bag| bag.num_red <= *num_red && bag.num_green <= *num_green)
+                .map(|bag| bag.num_blue)
+                .collect();
+            if admitted_blues.len() < min_games {
+                continue;
+            }
+            admitted_blues.sort();
+
+            // Enough blue cubes for the min_games games needing the fewest, and for the required games
+            let mut num_blue: i32 = floor.num_blue;
+            if min_games > 0 {
+                num_blue = cmp::max(num_blue, admitted_blues[min_games - 1]);
+            }
+            let bag: Bag = Bag::new(*num_red, *num_green, num_blue);
+            let bag_cost: i64 = bag_cost(&bag, cost);
+            let is_cheaper: bool = match &cheapest {
+                Some((cheapest_cost, _)) => bag_cost < *cheapest_cost,
+                None => true,
+            };
+            if is_cheaper {
+                cheapest = Some((bag_cost, bag));
+            }
+        }
+    }
+
+    // The largest candidates always admit every game, so there is always a cheapest bag
+    let bag: Bag = cheapest.unwrap().1;
+    let game_ids: Vec<i32> = games
+        .iter()
+        .filter(|game| is_game_possible(game, &bag))
+        .map(|game| game.id)
+        .collect();
+
+    return Ok(BagSelection {
+        bag: bag,
+        game_ids: game_ids,
+    });
+}
+
+fn candidate_counts(floor: i32, counts: impl Iterator<Item = i32>) -> Vec<i32> {
+    let mut candidates: Vec<i32> = vec![floor];
+    candidates.extend(counts.filter(|count| *count > floor));
+    candidates.sort();
+    candidates.dedup();
+    return candidates;
+}
+
+pub fn bag_cost(bag: &Bag, cost: &BagCost) -> i64 {
+    return match cost {
+        BagCost::TotalCubes => bag.num_red as i64 + bag.num_green as i64 + bag.num_blue as i64,
+        // Computed in i64 rather than with Bag::power, as the cheapest bag may have large counts
+        BagCost::Power => bag.num_red as i64 * bag.num_green as i64 * bag.num_blue as i64,
+    };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Game {
+    id: i32,
+    hands: Vec<Hand>,
+}
+
+impl Game {
+    pub fn new(id: i32, hands: Vec<Hand>) -> Game {
+        return Game {
+            id,
+            hands,
+        };
+    }
+
+    pub fn id(&self) -> i32 {
+        return self.id;
+    }
+
+    pub fn hands(&self) -> &Vec<Hand> {
+        return &self.hands;
+    }
+}
+
+// Written in the same format the puzzle input uses, so parse_game reads it back
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hands: Vec<String> = self.hands.iter().map(|hand| hand.to_string()).collect();
+        return write!(f, "Game {}: {}", self.id, hands.join("; "));
+    }
+}
+
+// How the size of a bag is measured when looking for the cheapest one
+pub enum BagCost {
+    TotalCubes,
+    Power,
+}
+
+// The cheapest bag found, and the ids of every game it makes possible
+pub struct BagSelection {
+    bag: Bag,
+    game_ids: Vec<i32>,
+}
+
+impl BagSelection {
+    pub fn bag(&self) -> &Bag {
+        return &self.bag;
+    }
+
+    pub fn game_ids(&self) -> &Vec<i32> {
+        return &self.game_ids;
+    }
+}
+
+// The example games from the puzzle, shared by the tests of every module
+#[cfg(test)]
+pub(crate) const TEST_GAMES: &str = "
+    Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+    Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+    Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+    Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+    Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test() {
+        let games: Vec<Game> = match parse_games(&TEST_GAMES.to_string()) {
+            Ok(res) => res,
+            Err(message) => panic!("{}", message),
+        };
+        assert_eq!(sum_powers_of_smallest_bags(&games), Ok(2286));
+
+        let games: Vec<Game> = parse_games(&"Game 1: 2000000000 red, 2000000000 green, 2000000000 blue\n".to_string()).unwrap();
+        assert!(sum_powers_of_smallest_bags(&games).is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+        assert_eq!(games[0].to_string(), "Game 1: 4 red, 0 green, 3 blue; 1 red, 2 green, 6 blue; 0 red, 2 green, 0 blue");
+        for game in &games {
+            assert_eq!(parse_game(&game.to_string()).unwrap(), *game);
+        }
+    }
+
+    #[test]
+    fn test_smallest_bag_for_game() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+        let smallest_bag: Bag = find_smallest_bag_for_game(&games[0]);
+        assert_eq!(smallest_bag, Bag::new(4, 2, 6));
+        assert!(games[0].hands.iter().all(|hand| *hand <= smallest_bag));
+    }
+
+    #[test]
+    fn test_cheapest_bag_for_min_games() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+
+        let selection: BagSelection = find_cheapest_bag_for_games(&games, 3, &Vec::new(), &BagCost::TotalCubes).unwrap();
+        assert_eq!(bag_cost(&selection.bag, &BagCost::TotalCubes), 6 + 3 + 6);
+        assert_eq!(selection.game_ids, vec![1, 2, 5]);
+
+        let selection: BagSelection = find_cheapest_bag_for_games(&games, 5, &Vec::new(), &BagCost::Power).unwrap();
+        assert_eq!(bag_cost(&selection.bag, &BagCost::Power), 20 * 13 * 15);
+        assert_eq!(selection.game_ids, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_cheapest_bag_for_required_games() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+
+        let selection: BagSelection = find_cheapest_bag_for_games(&games, 2, &vec![4], &BagCost::Power).unwrap();
+        assert!(selection.game_ids.contains(&4));
+        assert!(selection.game_ids.len() >= 2);
+        assert_eq!(bag_cost(&selection.bag, &BagCost::Power), 14 * 3 * 15);
+
+        assert!(find_cheapest_bag_for_games(&games, 0, &vec![6], &BagCost::Power).is_err());
+        assert!(find_cheapest_bag_for_games(&games, 6, &Vec::new(), &BagCost::Power).is_err());
+    }
+}
\ No newline at end of file
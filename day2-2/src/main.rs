@@ -1,20 +1,50 @@
-use std::cmp;
 use std::env;
 use std::fs;
-use regex::Regex;
-use regex::Captures;
+
+use day2_2::bag_config;
+use day2_2::bag_cost;
+use day2_2::find_cheapest_bag_for_games;
+use day2_2::formats;
+use day2_2::repl;
+use day2_2::report;
+use day2_2::validation;
+use day2_2::sum_powers_of_smallest_bags;
+use day2_2::Bag;
+use day2_2::BagCost;
+use day2_2::BagSelection;
+use day2_2::Game;
+
+use formats::GameFormat;
+use report::DayReport;
+use report::ReportFormat;
+use validation::ParseMode;
 
 fn main() {
     // Check command line arguments
     let argc: Vec<String> = env::args().collect();
     let argv: usize = argc.len();
     if argv < 2 {
-        println!("Usage: input_file num_red num_green num_blue");
+        println!("Usage: input_file [min_games (total|power) [required_game_id ...]]");
+        println!("       input_file report (text|csv|csv-totals|json) [num_red num_green num_blue]");
+        println!("       input_file report (text|csv|csv-totals|json) [--bag-file bag.toml|bag.json] [--red num_red] [--green num_green] [--blue num_blue]");
+        println!("       input_file dump ({})", formats::FORMAT_NAMES);
+        println!("       input_file validate [strict|relaxed] [sequential]");
+        println!("       repl input_file");
+        println!("Games are read as {} based on the input_file extension, and as text otherwise.", formats::FORMAT_NAMES);
+        println!("Report bag counts may also be set with the {}, {}, {} and {} environment variables.",
+            bag_config::BAG_FILE_VAR, bag_config::RED_VAR, bag_config::GREEN_VAR, bag_config::BLUE_VAR);
+        return;
+    }
+
+    // The shell is named before the input file, as in "repl input_file"
+    let is_repl: bool = argc[1] == "repl";
+    if is_repl && argv < 3 {
+        println!("Usage: repl input_file");
         return;
     }
 
     // Read input file
-    let input_file: &String = &argc[1];
+    let input_file: &String = if is_repl { &argc[2] } else { &argc[1] };
     let contents: String = match fs::read_to_string(input_file) {
         Err(e) => {
             eprintln!("Error reading input_file {}: {}", input_file, e);
@@ -22,157 +52,185 @@ fn main() {
         },
         Ok(f) => f,
     };
-    
-    let sum_of_smallest_game_powers = match sum_powers_of_smallest_game_bags(&contents) {
+
+    if !is_repl && argv > 2 && argc[2] == "validate" {
+        print_violations(&argc, &contents);
+        return;
+    }
+
+    let games: Vec<Game> = match formats::parse_games_as(&contents, &formats::format_for_path(input_file)) {
         Err(e) => {
-            eprintln!("Error calculating sum of powers of smallest game bags: {}", e);
+            eprintln!("Error parsing games: {}", e);
             return;
         },
         Ok(result) => result,
     };
 
-    println!("{}", sum_of_smallest_game_powers);
-}
-
-pub fn sum_powers_of_smallest_game_bags(description: &String) -> Result<i32, String> {
-    let mut sum_of_smallest_game_powers: i32 = 0;
-    for line in description.split("\n") {
-        // Skip empty lines
-        if line.is_empty() {
-            continue;
+    if is_repl {
+        if let Err(e) = repl::run(&games) {
+            eprintln!("{}", e);
         }
-        // Parse games
-        let game: Game = match parse_game(&line.to_string()) {
-            Err(e) => {
-                return Err(format!("Error parsing game {}: {}", line, e));
-            },
-            Ok(g) => g,
-        };
-        // Find smallest bag and add power to cumulative sum
-        let smallest_bag_for_game: Bag = find_smallest_bag_for_game(&game);
-        sum_of_smallest_game_powers += 
-            smallest_bag_for_game.num_red * smallest_bag_for_game.num_green * smallest_bag_for_game.num_blue; 
+        return;
     }
-    return Ok(sum_of_smallest_game_powers);
-}
 
-fn parse_game(description: &String) -> Result<Game, String> {
-    let game_parts: Vec<&str> = description.split(":").collect();
-    if game_parts.len() != 2 {
-        return Err(format!("Unexpected number of parts for game {}\nExpected: 2, Actual: {}", description, game_parts.len()));
+    if argv > 2 && argc[2] == "report" {
+        print_report(&argc, &games);
+        return;
     }
 
-    let game_hands: String = game_parts[1].to_string();
-
-    let mut hands: Vec<Hand> = Vec::new();
-    for game_hand_string in game_hands.split(";") {
-        match parse_hand(&game_hand_string.to_string()) {
-            Err(e) => {
-                return Err(e);
-            },
-            Ok(result) => {
-                hands.push(result);
-            },
-        };
+    if argv > 2 && argc[2] == "dump" {
+        print_dump(&argc, &games);
+        return;
     }
+    
+    let sum_of_smallest_game_powers: i64 = match sum_powers_of_smallest_bags(&games) {
+        Err(e) => {
+            eprintln!("Error calculating sum of powers: {}", e);
+            return;
+        },
+        Ok(result) => result,
+    };
 
-    return Ok(Game {
-        hands: hands
-    });
-}
+    println!("{}", sum_of_smallest_game_powers);
 
-fn parse_hand(description: &String) -> Result<Hand, String> {
-    let mut num_red: Option<i32> = None;
-    let mut num_green: Option<i32> = None;
-    let mut num_blue: Option<i32> = None;
+    // Optionally find the cheapest bag that makes at least min_games games possible
+    if argv < 4 {
+        return;
+    }
 
-    let colour_description_regex: Regex = Regex::new(r"([0-9]+) (red|green|blue)").unwrap();
+    let min_games: usize = match argc[2].parse::<usize>() {
+        Err(e) => {
+            eprintln!("Error parsing min_games {}: {}", argc[2], e);
+            return;
+        },
+        Ok(result) => result,
+    };
 
-    for colour_description in description.split(",") {
-        let trimmed = colour_description.trim();
-        // Check it's at all valid
-        let caps: Captures = match colour_description_regex.captures(trimmed) {
-            Some(caps) => caps,
-            None => {
-                return Err(format!("Malformed hand description {}", description)); 
+    let cost: BagCost = match argc[3].as_str() {
+        "total" => BagCost::TotalCubes,
+        "power" => BagCost::Power,
+        other => {
+            eprintln!("Unknown bag cost {}, expected total or power", other);
+            return;
+        },
+    };
+
+    let mut required_game_ids: Vec<i32> = Vec::new();
+    for required_game_id in &argc[4..] {
+        match required_game_id.parse::<i32>() {
+            Err(e) => {
+                eprintln!("Error parsing required_game_id {}: {}", required_game_id, e);
+                return;
             },
+            Ok(result) => required_game_ids.push(result),
         };
-        let num_cubes = caps[1].parse::<i32>().unwrap();
-        if trimmed.ends_with("red") {
-            if num_red.is_none() {
-                num_red = Some(num_cubes);
-            } else {
-                return Err(format!("Multiple instances of red for hand {}", description));
-            }
-        }
-        if trimmed.ends_with("green") {
-            if num_green.is_none() {
-                num_green = Some(num_cubes);
-            } else {
-                return Err(format!("Multiple instances of green for hand {}", description));
-            }
-        }
-        if trimmed.ends_with("blue") {
-            if num_blue.is_none() {
-                num_blue = Some(num_cubes);
-            } else {
-                return Err(format!("Multiple instances of blue for hand {}", description));
-            }
-        }
     }
 
-    return Ok(Hand {
-        num_red: num_red.unwrap_or(0),
-        num_green: num_green.unwrap_or(0),
-        num_blue: num_blue.unwrap_or(0),
-    });
-}
-
-fn find_smallest_bag_for_game(game: &Game) -> Bag {
-    let mut bag: Bag = Bag {
-        num_red: 0,
-        num_green: 0,
-        num_blue: 0
+    let selection: BagSelection = match find_cheapest_bag_for_games(&games, min_games, &required_game_ids, &cost) {
+        Err(e) => {
+            eprintln!("Error finding cheapest bag: {}", e);
+            return;
+        },
+        Ok(result) => result,
     };
 
-    for hand in &game.hands {
-        bag.num_red = cmp::max(bag.num_red, hand.num_red);
-        bag.num_green = cmp::max(bag.num_green, hand.num_green);
-        bag.num_blue = cmp::max(bag.num_blue, hand.num_blue);
+    println!(
+        "{} (cost {})",
+        selection.bag(), bag_cost(selection.bag(), &cost)
+    );
+    println!("{:?}", selection.game_ids());
+}
+
+fn print_report(argc: &Vec<String>, games: &Vec<Game>) {
+    if argc.len() < 4 {
+        println!("Usage: input_file report (text|csv|csv-totals|json) [num_red num_green num_blue]");
+        println!("       input_file report (text|csv|csv-totals|json) [--bag-file bag.toml|bag.json] [--red num_red] [--green num_green] [--blue num_blue]");
+        return;
     }
 
-    return bag;
-}
+    let format: ReportFormat = match argc[3].as_str() {
+        "text" => ReportFormat::Text,
+        "csv" => ReportFormat::Csv,
+        "csv-totals" => ReportFormat::CsvTotals,
+        "json" => ReportFormat::Json,
+        other => {
+            eprintln!("Unknown report format {}, expected text, csv, csv-totals or json", other);
+            return;
+        },
+    };
 
-pub struct Game {
-    hands: Vec<Hand>,
-}
+    // Read bag parameters, the same way as day2-1
+    let bag: Bag = match bag_config::load_bag(&argc[4..], |name| env::var(name).ok()) {
+        Err(e) => {
+            eprintln!("Error reading bag: {}", e);
+            return;
+        },
+        Ok(result) => result,
+    };
 
-pub struct Bag {
-    num_red: i32,
-    num_green: i32,
-    num_blue: i32,
+    let day_report: DayReport = match report::build_report(games, &bag) {
+        Err(e) => {
+            eprintln!("Error building report: {}", e);
+            return;
+        },
+        Ok(result) => result,
+    };
+    print!("{}", report::format_report(&day_report, &format));
 }
 
-// a given Hand is a subset of the contents of the Bag
-type Hand = Bag;
+fn print_dump(argc: &Vec<String>, games: &Vec<Game>) {
+    if argc.len() < 4 {
+        println!("Usage: input_file dump ({})", formats::FORMAT_NAMES);
+        return;
+    }
 
-#[cfg(test)]
-mod tests {
+    let format: GameFormat = match formats::format_for_name(&argc[3]) {
+        Some(format) => format,
+        None => {
+            eprintln!("Unknown game format {}, expected one of {}", argc[3], formats::FORMAT_NAMES);
+            return;
+        },
+    };
 
-    const TEST_GAMES: &str = "
-    Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-    Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-    Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-    Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-    Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+    match formats::write_games_as(games, &format) {
+        Err(e) => eprintln!("Error writing games: {}", e),
+        Ok(result) => print!("{}", result),
+    };
+}
 
-    #[test]
-    fn test() {
-        let result = match crate::sum_powers_of_smallest_game_bags(&TEST_GAMES.to_string()) {
-            Ok(res) => res,
-            Err(message) => panic!("{}", message),
+fn print_violations(argc: &Vec<String>, contents: &String) {
+    let mut relaxed: bool = false;
+    let mut sequential_ids: bool = false;
+    for option in &argc[3..] {
+        match option.as_str() {
+            "strict" => relaxed = false,
+            "relaxed" => relaxed = true,
+            "sequential" => sequential_ids = true,
+            other => {
+                eprintln!("Unknown validate option {}, expected strict, relaxed or sequential", other);
+                return;
+            },
         };
-        assert_eq!(result, 2286);
     }
-}
\ No newline at end of file
+
+    let mode: ParseMode = if relaxed {
+        // Relaxed mode doesn't look at ids beyond parsing them
+        if sequential_ids {
+            eprintln!("sequential can only be used with strict validation");
+            return;
+        }
+        ParseMode::Relaxed
+    } else {
+        ParseMode::Strict { sequential_ids }
+    };
+
+    match validation::parse_games_with_mode(contents, &mode) {
+        Ok(games) => println!("{} games, no violations", games.len()),
+        Err(violations) => {
+            for violation in &violations {
+                println!("{}", violation);
+            }
+            println!("{} violations", violations.len());
+        },
+    };
+}
@@ -0,0 +1,219 @@
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::Context;
+use rustyline::Editor;
+use rustyline::Helper;
+
+use crate::bag_config::parse_count;
+use crate::find_smallest_bag_for_game;
+use crate::is_game_possible;
+use crate::smallest_bag_power;
+use crate::sum_powers_of_smallest_bags;
+use crate::Bag;
+use crate::Game;
+
+const COMMANDS: [&str; 7] = ["show", "minbag", "possible", "top", "stats", "help", "quit"];
+const TOP_METRICS: [&str; 3] = ["power", "total", "hands"];
+
+const HELP: &str = "Commands:
+  show ID                      print the hands of a game
+  minbag ID                    print the smallest bag that makes a game possible, and its power
+  possible RED GREEN BLUE      list the games possible with the given bag, and the sum of their ids
+  top (power|total|hands) N    list the N games with the largest smallest-bag power, total cubes, or number of hands
+  stats                        print summary statistics for all games
+  help                         print this message
+  quit                         leave the shell";
+
+// Runs an interactive shell over games that have already been parsed, until the user quits or closes input
+pub fn run(games: &Vec<Game>) -> Result<(), String> {
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Err(e) => {
+            return Err(format!("Error starting shell: {}", e));
+        },
+        Ok(result) => result,
+    };
+    editor.set_helper(Some(ReplHelper {
+        game_ids: games.iter().map(|game| game.id().to_string()).collect(),
+    }));
+
+    println!("Loaded {} games, type help for a list of commands", games.len());
+    loop {
+        let line: String = match editor.readline("day2> ") {
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                return Err(format!("Error reading command: {}", e));
+            },
+            Ok(result) => result,
+        };
+
+        let command: &str = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        // History is only for convenience, so failing to record an entry isn't worth stopping for
+        let _ = editor.add_history_entry(command);
+
+        if command == "quit" || command == "exit" {
+            break;
+        }
+        match run_command(games, command) {
+            Err(e) => eprintln!("{}", e),
+            Ok(output) => println!("{}", output),
+        };
+    }
+
+    return Ok(());
+}
+
+// Evaluates a single shell command against the games, returning the text to print
+pub fn run_command(games: &Vec<Game>, command: &str) -> Result<String, String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    return match words.as_slice() {
+        ["show", id] => {
+            let game: &Game = find_game(games, id)?;
+            Ok(game.to_string())
+        },
+        ["minbag", id] => {
+            let game: &Game = find_game(games, id)?;
+            let smallest_bag: Bag = find_smallest_bag_for_game(game);
+            Ok(format!("{} (power {})", smallest_bag, smallest_bag_power(game)?))
+        },
+        ["possible", num_red, num_green, num_blue] => {
+            // Counts are checked as for a bag given on the command line
+            let bag: Bag = Bag::new(parse_count("red", num_red)?, parse_count("green", num_green)?, parse_count("blue", num_blue)?);
+            let game_ids: Vec<i32> = games
+                .iter()
+                .filter(|game| is_game_possible(game, &bag))
+                .map(|game| game.id())
+                .collect();
+            Ok(format!("{} games possible, ids sum to {}\n{:?}", game_ids.len(), game_ids.iter().sum::<i32>(), game_ids))
+        },
+        ["top", metric, count] => {
+            let count: usize = parse_number(count)?;
+            let mut ranked: Vec<(i64, i32)> = Vec::new();
+            for game in games {
+                let smallest_bag: Bag = find_smallest_bag_for_game(game);
+                let value: i64 = match *metric {
+                    "power" => smallest_bag_power(game)?,
+                    "total" => (smallest_bag.num_red() + smallest_bag.num_green() + smallest_bag.num_blue()) as i64,
+                    "hands" => game.hands().len() as i64,
+                    _ => {
+                        return Err(format!("Unknown metric {}, expected one of {}", metric, TOP_METRICS.join(", ")));
+                    },
+                };
+                ranked.push((value, game.id()));
+            }
+            // Largest first, with ties broken by game id
+            ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            let lines: Vec<String> = ranked
+                .iter()
+                .take(count)
+                .map(|(value, id)| format!("Game {}: {}", id, value))
+                .collect();
+            Ok(lines.join("\n"))
+        },
+        ["stats"] => format_stats(games),
+        ["help"] => Ok(HELP.to_string()),
+        _ => Err(format!("Unknown command {}, type help for a list of commands", command)),
+    };
+}
+
+fn format_stats(games: &Vec<Game>) -> Result<String, String> {
+    if games.is_empty() {
+        return Ok("No games loaded".to_string());
+    }
+
+    let mut num_hands: usize = 0;
+    let mut largest_bag: Bag = Bag::default();
+    let mut powers: Vec<i64> = Vec::new();
+    for game in games {
+        num_hands += game.hands().len();
+        largest_bag = largest_bag.join(&find_smallest_bag_for_game(game));
+        powers.push(smallest_bag_power(game)?);
+    }
+
+    let sum_of_powers: i64 = sum_powers_of_smallest_bags(games)?;
+    return Ok(format!(
+        "games: {}\nhands: {}\nbag for every game: {}\npower: min {}, max {}, mean {:.2}, sum {}",
+        games.len(),
+        num_hands,
+        largest_bag,
+        powers.iter().min().unwrap(),
+        powers.iter().max().unwrap(),
+        sum_of_powers as f64 / games.len() as f64,
+        sum_of_powers
+    ));
+}
+
+fn find_game<'a>(games: &'a Vec<Game>, id: &str) -> Result<&'a Game, String> {
+    let id: i32 = parse_number(id)?;
+    return match games.iter().find(|game| game.id() == id) {
+        Some(game) => Ok(game),
+        None => Err(format!("No game with id {}", id)),
+    };
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    return value.parse::<T>().map_err(|_| format!("Expected a number, got {}", value));
+}
+
+// Completes command names, then the metric for top and the game id for show and minbag
+struct ReplHelper {
+    game_ids: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let line: &str = &line[..pos];
+        let start: usize = line.rfind(' ').map_or(0, |space| space + 1);
+        let previous_words: Vec<&str> = line[..start].split_whitespace().collect();
+        let word: &str = &line[start..];
+
+        let options: Vec<String> = match previous_words.as_slice() {
+            [] => COMMANDS.iter().map(|command| command.to_string()).collect(),
+            ["top"] => TOP_METRICS.iter().map(|metric| metric.to_string()).collect(),
+            ["show"] | ["minbag"] => self.game_ids.clone(),
+            _ => Vec::new(),
+        };
+
+        let candidates: Vec<String> = options.into_iter().filter(|option| option.starts_with(word)).collect();
+        return Ok((start, candidates));
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::TEST_GAMES;
+    use crate::parse_games;
+
+    #[test]
+    fn test_commands() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+
+        assert_eq!(run_command(&games, "minbag 1").unwrap(), "4 red, 2 green, 6 blue (power 48)");
+        assert!(run_command(&games, "possible 12 13 14").unwrap().starts_with("3 games possible, ids sum to 8"));
+        assert!(run_command(&games, "possible 12 -13 14").is_err());
+        assert_eq!(run_command(&games, "top power 2").unwrap(), "Game 3: 1560\nGame 4: 630");
+        assert!(run_command(&games, "stats").unwrap().contains("sum 2286"));
+        assert!(run_command(&games, "show 6").is_err());
+        assert!(run_command(&games, "top colour 2").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::find_smallest_bag_for_game;
+use crate::is_game_possible;
+use crate::smallest_bag_power;
+use crate::Bag;
+use crate::Game;
+
+// Everything we know about each game after a single parse, along with the answers to both parts
+pub struct DayReport {
+    games: Vec<GameReport>,
+    sum_of_possible_game_ids: i32,
+    sum_of_smallest_game_powers: i64,
+}
+
+pub struct GameReport {
+    id: i32,
+    num_hands: usize,
+    smallest_bag: Bag,
+    power: i64,
+    possible: bool,
+}
+
+pub enum ReportFormat {
+    Text,
+    // One row per game
+    Csv,
+    // The part answers, as a separate file with its own header, since they don't fit the per-game columns
+    CsvTotals,
+    Json,
+}
+
+pub fn build_report(games: &Vec<Game>, bag: &Bag) -> Result<DayReport, String> {
+    let mut game_reports: Vec<GameReport> = Vec::new();
+    let mut sum_of_possible_game_ids: i32 = 0;
+    let mut sum_of_smallest_game_powers: i64 = 0;
+
+    for game in games {
+        let smallest_bag: Bag = find_smallest_bag_for_game(game);
+        let power: i64 = smallest_bag_power(game)?;
+        let possible: bool = is_game_possible(game, bag);
+
+        if possible {
+            sum_of_possible_game_ids += game.id;
+        }
+        sum_of_smallest_game_powers = match sum_of_smallest_game_powers.checked_add(power) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of powers overflowed".to_string());
+            },
+        };
+
+        game_reports.push(GameReport {
+            id: game.id,
+            num_hands: game.hands.len(),
+            smallest_bag,
+            power,
+            possible,
+        });
+    }
+
+    return Ok(DayReport {
+        games: game_reports,
+        sum_of_possible_game_ids,
+        sum_of_smallest_game_powers,
+    });
+}
+
+pub fn format_report(report: &DayReport, format: &ReportFormat) -> String {
+    return match format {
+        ReportFormat::Text => format_text(report),
+        ReportFormat::Csv => format_csv(report),
+        ReportFormat::CsvTotals => format_csv_totals(report),
+        ReportFormat::Json => format_json(report),
+    };
+}
+
+fn format_text(report: &DayReport) -> String {
+    let mut output: String = format!(
+        "{:>6} {:>6} {:>6} {:>6} {:>6} {:>8} {:>9}\n",
+        "id", "hands", "red", "green", "blue", "power", "possible"
+    );
+    for game in &report.games {
+        output += &format!(
+            "{:>6} {:>6} {:>6} {:>6} {:>6} {:>8} {:>9}\n",
+            game.id,
+            game.num_hands,
+            game.smallest_bag.num_red,
+            game.smallest_bag.num_green,
+            game.smallest_bag.num_blue,
+            game.power,
+            game.possible
+        );
+    }
+    output += &format!("\nPart 1: {}\n", report.sum_of_possible_game_ids);
+    output += &format!("Part 2: {}\n", report.sum_of_smallest_game_powers);
+    return output;
+}
+
+fn format_csv(report: &DayReport) -> String {
+    let mut output: String = "id,hands,red,green,blue,power,possible\n".to_string();
+    for game in &report.games {
+        output += &format!(
+            "{},{},{},{},{},{},{}\n",
+            game.id,
+            game.num_hands,
+            game.smallest_bag.num_red,
+            game.smallest_bag.num_green,
+            game.smallest_bag.num_blue,
+            game.power,
+            game.possible
+        );
+    }
+    return output;
+}
+
+fn format_csv_totals(report: &DayReport) -> String {
+    let mut output: String = "part,answer\n".to_string();
+    output += &format!("1,{}\n", report.sum_of_possible_game_ids);
+    output += &format!("2,{}\n", report.sum_of_smallest_game_powers);
+    return output;
+}
+
+fn format_json(report: &DayReport) -> String {
+    let mut game_entries: Vec<String> = Vec::new();
+    for game in &report.games {
+        game_entries.push(format!(
+            "    {{\"id\": {}, \"hands\": {}, \"smallest_bag\": {{\"red\": {}, \"green\": {}, \"blue\": {}}}, \"power\": {}, \"possible\": {}}}",
+            game.id,
+            game.num_hands,
+            game.smallest_bag.num_red,
+            game.smallest_bag.num_green,
+            game.smallest_bag.num_blue,
+            game.power,
+            game.possible
+        ));
+    }
+
+    let mut output: String = "{\n  \"games\": [\n".to_string();
+    output += &game_entries.join(",\n");
+    output += "\n  ],\n";
+    output += &format!("  \"part1\": {},\n", report.sum_of_possible_game_ids);
+    output += &format!("  \"part2\": {}\n", report.sum_of_smallest_game_powers);
+    output += "}\n";
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::TEST_GAMES;
+    use crate::parse_games;
+
+    const TEST_BAG: Bag = Bag {
+        num_red: 12,
+        num_green: 13,
+        num_blue: 14,
+    };
+
+    #[test]
+    fn test() {
+        let games: Vec<Game> = parse_games(&TEST_GAMES.to_string()).unwrap();
+        let report: DayReport = build_report(&games, &TEST_BAG).unwrap();
+        assert_eq!(report.sum_of_possible_game_ids, 8);
+        assert_eq!(report.sum_of_smallest_game_powers, 2286);
+
+        let csv: String = format_report(&report, &ReportFormat::Csv);
+        assert!(csv.contains("\n3,3,20,13,6,1560,false\n"));
+        assert!(csv.lines().all(|line| line.split(",").count() == 7));
+        assert_eq!(format_report(&report, &ReportFormat::CsvTotals), "part,answer\n1,8\n2,2286\n");
+    }
+}
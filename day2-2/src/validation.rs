@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fmt;
+use regex::Regex;
+
+use crate::parse_game;
+use crate::Game;
+use crate::Hand;
+
+// How closely a games description must follow the puzzle input format
+pub enum ParseMode {
+    // Accepts anything parse_games does, e.g. stray text around game headers
+    Relaxed,
+    // Requires exactly "Game X: N colour, N colour; N colour" on every line, with unique game ids,
+    // which must also count up from 1 if sequential_ids is set
+    Strict { sequential_ids: bool },
+}
+
+// A single problem found in a games description
+#[derive(Debug, PartialEq, Eq)]
+pub struct Violation {
+    line_number: usize,
+    message: String,
+}
+
+impl Violation {
+    pub fn line_number(&self) -> usize {
+        return self.line_number;
+    }
+
+    pub fn message(&self) -> &String {
+        return &self.message;
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "line {}: {}", self.line_number, self.message);
+    }
+}
+
+pub fn parse_games_with_mode(description: &String, mode: &ParseMode) -> Result<Vec<Game>, Vec<Violation>> {
+    return match mode {
+        ParseMode::Relaxed => parse_games_relaxed(description),
+        ParseMode::Strict { sequential_ids } => parse_games_strict(description, *sequential_ids),
+    };
+}
+
+// As parse_games, but reporting every line it rejects against that line's number
+fn parse_games_relaxed(description: &String) -> Result<Vec<Game>, Vec<Violation>> {
+    let mut games: Vec<Game> = Vec::new();
+    let mut violations: Vec<Violation> = Vec::new();
+
+    for (index, line) in description.split("\n").enumerate() {
+        // Skip empty lines
+        if line.is_empty() {
+            continue;
+        }
+        match parse_game(&line.to_string()) {
+            Err(e) => violations.push(Violation {
+                line_number: index + 1,
+                message: format!("Error parsing game {}: {}", line, e),
+            }),
+            Ok(game) => games.push(game),
+        };
+    }
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+    return Ok(games);
+}
+
+// Checks every line rather than stopping at the first problem, so all violations can be reported at once
+fn parse_games_strict(description: &String, sequential_ids: bool) -> Result<Vec<Game>, Vec<Violation>> {
+    let game_header_regex: Regex = Regex::new(r"^Game ([0-9]+)$").unwrap();
+    let colour_description_regex: Regex = Regex::new(r"^([0-9]+) (red|green|blue)$").unwrap();
+
+    let mut games: Vec<Game> = Vec::new();
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut first_line_for_id: HashMap<i32, usize> = HashMap::new();
+    let mut previous_id: Option<i32> = None;
+
+    let lines: Vec<&str> = description.split("\n").collect();
+    for (index, line) in lines.iter().enumerate() {
+        let line_number: usize = index + 1;
+        let line: &str = line.strip_suffix("\r").unwrap_or(line);
+        let mut line_violations: Vec<String> = Vec::new();
+
+        // Only the newline ending the final line is allowed to leave an empty line behind
+        if line.is_empty() {
+            if index + 1 != lines.len() {
+                violations.push(Violation {
+                    line_number,
+                    message: "Empty line".to_string(),
+                });
+            }
+            continue;
+        }
+
+        let (game_header, game_hands) = match line.split_once(":") {
+            Some(parts) => parts,
+            None => {
+                violations.push(Violation {
+                    line_number,
+                    message: format!("Expected \"Game X: \" header in {}", line),
+                });
+                continue;
+            },
+        };
+        // A missing space is reported on its own, so the rest of the line is still checked
+        let game_hands: &str = match game_hands.strip_prefix(" ") {
+            Some(game_hands) => game_hands,
+            None => {
+                if !game_hands.is_empty() {
+                    line_violations.push(format!("Expected a space after the colon in {}", line));
+                }
+                game_hands
+            },
+        };
+
+        let mut id: Option<i32> = None;
+        match game_header_regex.captures(game_header) {
+            Some(caps) => match caps[1].parse::<i32>() {
+                Ok(result) => id = Some(result),
+                Err(e) => line_violations.push(format!("Game id {} out of range: {}", &caps[1], e)),
+            },
+            None => line_violations.push(format!("Malformed game header {}", game_header)),
+        };
+
+        if let Some(id) = id {
+            match first_line_for_id.get(&id) {
+                Some(first_line) => line_violations.push(format!("Duplicate game id {}, first used on line {}", id, first_line)),
+                None => {
+                    first_line_for_id.insert(id, line_number);
+                },
+            };
+            if sequential_ids {
+                let expected_id: i32 = previous_id.map_or(1, |previous_id| previous_id + 1);
+                if id != expected_id {
+                    line_violations.push(format!("Game id {} out of sequence, expected {}", id, expected_id));
+                }
+            }
+            previous_id = Some(id);
+        }
+
+        let mut hands: Vec<Hand> = Vec::new();
+        if game_hands.is_empty() {
+            line_violations.push("Game has no hands".to_string());
+        }
+        for (hand_index, game_hand_string) in game_hands.split("; ").enumerate() {
+            if game_hands.is_empty() {
+                break;
+            }
+            if game_hand_string.is_empty() {
+                line_violations.push(format!("Hand {} is empty", hand_index + 1));
+                continue;
+            }
+
+            let mut counts: HashMap<&str, i32> = HashMap::new();
+            for colour_description in game_hand_string.split(", ") {
+                let caps = match colour_description_regex.captures(colour_description) {
+                    Some(caps) => caps,
+                    None => {
+                        line_violations.push(format!("Malformed cube count \"{}\" in hand {}", colour_description, hand_index + 1));
+                        continue;
+                    },
+                };
+                let colour: &str = caps.get(2).unwrap().as_str();
+                let num_cubes: i32 = match caps[1].parse::<i32>() {
+                    Ok(result) => result,
+                    Err(e) => {
+                        line_violations.push(format!("Cube count {} out of range in hand {}: {}", &caps[1], hand_index + 1, e));
+                        continue;
+                    },
+                };
+                if counts.insert(colour, num_cubes).is_some() {
+                    line_violations.push(format!("Multiple instances of {} in hand {}", colour, hand_index + 1));
+                }
+            }
+
+            hands.push(Hand::new(
+                *counts.get("red").unwrap_or(&0),
+                *counts.get("green").unwrap_or(&0),
+                *counts.get("blue").unwrap_or(&0),
+            ));
+        }
+
+        if line_violations.is_empty() {
+            games.push(Game::new(id.unwrap(), hands));
+        }
+        for message in line_violations {
+            violations.push(Violation {
+                line_number,
+                message,
+            });
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+    return Ok(games);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::parse_games;
+
+    const STRICT: ParseMode = ParseMode::Strict { sequential_ids: false };
+    const SEQUENTIAL: ParseMode = ParseMode::Strict { sequential_ids: true };
+
+    #[test]
+    fn test_strict() {
+        let description: String = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green\n".to_string();
+        let games: Vec<Game> = parse_games_with_mode(&description, &SEQUENTIAL).unwrap();
+        assert_eq!(games, parse_games(&description).unwrap());
+    }
+
+    #[test]
+    fn test_strict_violations() {
+        let description: String = [
+            "xxGame 5yy: 1 red",
+            "Game 1: 3 blue extra; ; 1 red, 2 red",
+            "Game 1:",
+            "",
+            "Game 3: 1 green",
+        ].join("\n");
+
+        // The relaxed parser accepts stray text around the header, but not around cube counts
+        assert!(parse_games_with_mode(&"xxGame 5yy: 3 blue".to_string(), &ParseMode::Relaxed).is_ok());
+        assert!(parse_games_with_mode(&"xxGame 5yy: 3 blue extra".to_string(), &ParseMode::Relaxed).is_err());
+
+        let violations: Vec<Violation> = parse_games_with_mode(&description, &STRICT).unwrap_err();
+        let lines: Vec<usize> = violations.iter().map(|violation| violation.line_number()).collect();
+        assert_eq!(lines, vec![1, 2, 2, 2, 3, 3, 4]);
+        assert_eq!(violations[2].message(), "Hand 2 is empty");
+        assert_eq!(violations[4].message(), "Duplicate game id 1, first used on line 2");
+        assert_eq!(violations[5].message(), "Game has no hands");
+
+        // "Game 1:" is checked the same way with or without the trailing space
+        let trailing_space: Vec<Violation> = parse_games_with_mode(&description.replace("Game 1:\n", "Game 1: \n"), &STRICT).unwrap_err();
+        assert_eq!(trailing_space, violations);
+        let violations: Vec<Violation> = parse_games_with_mode(&"Game 1:1 red\n".to_string(), &STRICT).unwrap_err();
+        assert_eq!(violations.len(), 1);
+
+        // Relaxed mode reports each line it can't parse against that line
+        let violations: Vec<Violation> = parse_games_with_mode(&"Game 1: 1 red\n\nGame 2 1 red\nGame 3: x\n".to_string(), &ParseMode::Relaxed).unwrap_err();
+        let lines: Vec<usize> = violations.iter().map(|violation| violation.line_number()).collect();
+        assert_eq!(lines, vec![3, 4]);
+
+        let violations: Vec<Violation> = parse_games_with_mode(&description, &SEQUENTIAL).unwrap_err();
+        assert!(violations.contains(&Violation {
+            line_number: 5,
+            message: "Game id 3 out of sequence, expected 2".to_string(),
+        }));
+    }
+}
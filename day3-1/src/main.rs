@@ -1,12 +1,20 @@
 use std::env;
 use std::fs;
+use std::io;
+use grid::Grid;
+
+use day3_2::render;
+use day3_2::Adjacency;
+use day3_2::ColumnModel;
+use day3_2::GearRules;
+use day3_2::NumberFormat;
 
 fn main() {
     // Check command line arguments
     let argc: Vec<String> = env::args().collect();
     let argv: usize = argc.len();
     if argv < 2 {
-        println!("Usage: input_file num_red num_green num_blue");
+        println!("Usage: input_file [--render [--no-colour]]");
         return;
     }
 
@@ -20,6 +28,27 @@ fn main() {
         Ok(f) => f,
     };
 
+    // Rendering is shared with part 2, so shows gears too
+    if argc[2..].contains(&"--render".to_string()) {
+        let render_options: render::RenderOptions = render::RenderOptions {
+            colour: !argc[2..].contains(&"--no-colour".to_string()),
+        };
+        let mut output = io::BufWriter::new(io::stdout().lock());
+        let result: Result<(), String> = render::render_schematic(
+            &mut output,
+            &contents,
+            &ColumnModel::default(),
+            &NumberFormat::default(),
+            &GearRules::default(),
+            &Adjacency::default(),
+            &render_options,
+        );
+        if let Err(e) = result {
+            eprintln!("Error rendering schematic: {}", e);
+        }
+        return;
+    }
+
     let result: usize = match sum_part_numbers_from_engine_schematic(&contents) {
         Ok(result) => result,
         Err(message) => {
@@ -32,14 +61,40 @@ fn main() {
 }
 
 fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<usize, String> {
+    // Short rows are padded with blank cells, which can't hold parts or part numbers
+    let schematic: Grid<char> = Grid::parse_padded(engine_schematic, '.');
+    let (parts, part_numbers) = parse_engine_schematic(&schematic)?;
+
+    // For each part, mark every part number touching it as associated
+    let part_number_index: Grid<Option<usize>> = build_part_number_index(&schematic, &part_numbers);
+    let mut is_associated: Vec<bool> = vec![false; part_numbers.len()];
+
+    for part in &parts {
+        for part_number_id in adjacent_part_number_ids(&part_number_index, part) {
+            is_associated[part_number_id] = true;
+        }
+    }
+
+    // Sum the resulting associated part numbers
+    let mut sum: usize = 0;
+    for (part_number_id, part_number) in part_numbers.iter().enumerate() {
+        if is_associated[part_number_id] {
+            sum += part_number.value;
+        }
+    }
+
+    return Ok(sum);
+}
+
+fn parse_engine_schematic(schematic: &Grid<char>) -> Result<(Vec<EnginePart>, Vec<PartNumber>), String> {
     // Parse through the lines of the schematic
     let mut parts: Vec<EnginePart> = Vec::new();
     let mut part_numbers: Vec<PartNumber> = Vec::new();
 
     let mut parsing_state: Option<PartNumberParsingState> = None;
 
-    for (y, line) in engine_schematic.split("\n").enumerate() {
-        for (x, character) in line.chars().enumerate() {
+    for (y, row) in schematic.rows().enumerate() {
+        for (x, character) in row.iter().copied().enumerate() {
             // If we've just stopped parsing a character, parse the result and commit.
             if !is_digit(character) && parsing_state.is_some() {
                 match parsing_state {
@@ -76,7 +131,7 @@ fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<u
         // If we're still parsing a part number at the end of the line, parse the result and commit.
         if parsing_state.is_some() {
             match parsing_state {
-                Some(existing) => match from_parsing_state(&existing, line.chars().count(), y) {
+                Some(existing) => match from_parsing_state(&existing, schematic.width(), y) {
                     Ok(result) => part_numbers.push(result),
                     Err(e) => return Err(e),
                 },
@@ -86,11 +141,18 @@ fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<u
         };
     }
 
+    return Ok((parts, part_numbers));
+}
+
+// Reference implementation of the part number sum, comparing every part number against every part.
+// Kept to check the indexed implementation against.
+#[cfg(test)]
+fn sum_associated_part_numbers_by_comparison(parts: &Vec<EnginePart>, part_numbers: Vec<PartNumber>) -> usize {
     // For each part number determine if it's associated with a part
     let mut associated_part_numbers: Vec<PartNumber> = Vec::new();
 
     for part_number in part_numbers {
-        if has_associated_part(&part_number, &parts) {
+        if has_associated_part(&part_number, parts) {
             associated_part_numbers.push(part_number);
         }
     }
@@ -101,7 +163,7 @@ fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<u
         sum += part_number.value;
     }
 
-    return Ok(sum);
+    return sum;
 }
 
 fn from_parsing_state(
@@ -135,6 +197,36 @@ fn is_engine_part(character: char) -> bool {
     return !is_digit(character) && character != '.';
 }
 
+// Maps each cell of the schematic to the index of the part number covering it, if any,
+// so the part numbers around a part can be looked up without checking every part number.
+fn build_part_number_index(schematic: &Grid<char>, part_numbers: &Vec<PartNumber>) -> Grid<Option<usize>> {
+    let mut part_number_index: Grid<Option<usize>> = Grid::new(schematic.width(), schematic.height(), None);
+
+    for (part_number_id, part_number) in part_numbers.iter().enumerate() {
+        for x in part_number.x_start_loc..part_number.x_end_loc {
+            part_number_index[(x, part_number.y_loc)] = Some(part_number_id);
+        }
+    }
+
+    return part_number_index;
+}
+
+// The indices of the part numbers adjacent (including diagonally) to the part, each listed once
+fn adjacent_part_number_ids(part_number_index: &Grid<Option<usize>>, part: &EnginePart) -> Vec<usize> {
+    let mut part_number_ids: Vec<usize> = Vec::new();
+
+    for (x, y) in part_number_index.neighbours8(part.x_loc, part.y_loc) {
+        if let Some(part_number_id) = part_number_index[(x, y)] {
+            if !part_number_ids.contains(&part_number_id) {
+                part_number_ids.push(part_number_id);
+            }
+        }
+    }
+
+    return part_number_ids;
+}
+
+#[cfg(test)]
 fn has_associated_part(part_number: &PartNumber, parts: &Vec<EnginePart>) -> bool {
     for part in parts {
         if is_part_number_for_part(part_number, &part) {
@@ -145,6 +237,7 @@ fn has_associated_part(part_number: &PartNumber, parts: &Vec<EnginePart>) -> boo
     return false;
 }
 
+#[cfg(test)]
 fn is_part_number_for_part(part_number: &PartNumber, part: &EnginePart) -> bool {
     // A part number is associated to a part if any part of it is adjacent (including diagonally) to the part.
 
@@ -187,6 +280,10 @@ struct PartNumber {
 mod tests {
 
     use std::fs;
+    use grid::Grid;
+    use day3_2::generator;
+    use generator::GeneratedSchematic;
+    use generator::GeneratorOptions;
 
     #[test]
     fn test() {
@@ -203,4 +300,34 @@ mod tests {
         };
         assert_eq!(result, 4361);
     }
+
+    #[test]
+    fn test_indexed_matches_comparison() {
+        // Generated schematics, dense enough that numbers regularly share parts and touch edges
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 20,
+            height: 20,
+            number_density: 0.4,
+            symbol_density: 0.3,
+            symbols: vec!['#'],
+            gear_frequency: 0.5,
+        };
+        for seed in 0..50 {
+            let engine_schematic: String = generator::generate_schematic(seed, &options).unwrap().engine_schematic;
+            let schematic: Grid<char> = Grid::parse(&engine_schematic).unwrap();
+            let (parts, part_numbers) = crate::parse_engine_schematic(&schematic).unwrap();
+            let expected: usize = crate::sum_associated_part_numbers_by_comparison(&parts, part_numbers);
+            assert_eq!(crate::sum_part_numbers_from_engine_schematic(&engine_schematic).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_matches_generated_reference() {
+        let options: GeneratorOptions = GeneratorOptions::default();
+        for seed in 0..10 {
+            let generated: GeneratedSchematic = generator::generate_schematic(seed, &options).unwrap();
+            let result: usize = crate::sum_part_numbers_from_engine_schematic(&generated.engine_schematic).unwrap();
+            assert_eq!(result as i64, generated.part_number_sum);
+        }
+    }
 }
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use crate::analyse_engine_schematic;
+use crate::graph::PartNode;
+use crate::graph::PartNumberNode;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::AnalysedSchematic;
+use crate::GearRules;
+use crate::NumberFormat;
+
+// Everything that was left out of the answers, or that might have been miscounted
+pub struct Audit {
+    // Part numbers not adjacent to any part, so excluded from the part 1 sum
+    pub unattached_part_numbers: Vec<PartNumberNode>,
+    // Parts not adjacent to any part number
+    pub isolated_parts: Vec<PartNode>,
+    // Parts with a gear symbol whose number of adjacent part numbers the gear rules don't allow
+    pub mismatched_gears: Vec<MismatchedGear>,
+    // One entry per distinct part symbol, in order of first appearance
+    pub symbol_breakdowns: Vec<SymbolBreakdown>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MismatchedGear {
+    pub part: PartNode,
+    pub neighbour_count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolBreakdown {
+    pub symbol: char,
+    pub count: usize,
+    // Distinct part numbers adjacent to at least one part with this symbol, and the sum of their values
+    pub attached_part_numbers: usize,
+    pub attached_sum: i64,
+}
+
+pub fn audit_schematic(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+) -> Result<Audit, String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+
+    let unattached_part_numbers: Vec<PartNumberNode> = analysed_schematic
+        .part_numbers
+        .iter()
+        .zip(&analysed_schematic.is_attached)
+        .filter(|(_, is_attached)| !**is_attached)
+        .map(|(part_number, _)| PartNumberNode::from(part_number))
+        .collect();
+
+    let mut isolated_parts: Vec<PartNode> = Vec::new();
+    let mut mismatched_gears: Vec<MismatchedGear> = Vec::new();
+    let mut symbol_breakdowns: Vec<SymbolBreakdown> = Vec::new();
+    // For each symbol breakdown, which part numbers have already been counted towards it
+    let mut counted_part_numbers: Vec<HashSet<usize>> = Vec::new();
+
+    for (part, part_number_ids) in analysed_schematic.parts.iter().zip(&analysed_schematic.adjacent_part_number_ids) {
+        if part_number_ids.is_empty() {
+            isolated_parts.push(PartNode::from(part));
+        }
+        if gear_rules.symbols.contains(&part.value) && !gear_rules.neighbour_count.allows(part_number_ids.len()) {
+            mismatched_gears.push(MismatchedGear {
+                part: PartNode::from(part),
+                neighbour_count: part_number_ids.len(),
+            });
+        }
+
+        let breakdown_id: usize = match symbol_breakdowns.iter().position(|breakdown| breakdown.symbol == part.value) {
+            Some(breakdown_id) => breakdown_id,
+            None => {
+                symbol_breakdowns.push(SymbolBreakdown {
+                    symbol: part.value,
+                    count: 0,
+                    attached_part_numbers: 0,
+                    attached_sum: 0,
+                });
+                counted_part_numbers.push(HashSet::new());
+                symbol_breakdowns.len() - 1
+            },
+        };
+        let breakdown: &mut SymbolBreakdown = &mut symbol_breakdowns[breakdown_id];
+        breakdown.count += 1;
+        for part_number_id in part_number_ids {
+            if counted_part_numbers[breakdown_id].insert(*part_number_id) {
+                breakdown.attached_part_numbers += 1;
+                breakdown.attached_sum = match breakdown.attached_sum.checked_add(analysed_schematic.part_numbers[*part_number_id].value) {
+                    Some(sum) => sum,
+                    None => {
+                        return Err(format!("Sum of part numbers attached to {} overflowed", part.value));
+                    },
+                };
+            }
+        }
+    }
+
+    return Ok(Audit {
+        unattached_part_numbers,
+        isolated_parts,
+        mismatched_gears,
+        symbol_breakdowns,
+    });
+}
+
+pub fn format_audit(audit: &Audit) -> String {
+    let mut output: String = String::new();
+
+    output += &format!("Unattached part numbers: {}\n", audit.unattached_part_numbers.len());
+    for part_number in &audit.unattached_part_numbers {
+        output += &format!(
+            "  {} at ({}..{}, {})\n",
+            part_number.value, part_number.x_start_loc, part_number.x_end_loc, part_number.y_loc
+        );
+    }
+
+    output += &format!("Isolated parts: {}\n", audit.isolated_parts.len());
+    for part in &audit.isolated_parts {
+        output += &format!("  {} at ({}, {})\n", part.value, part.x_loc, part.y_loc);
+    }
+
+    output += &format!("Gears with the wrong neighbour count: {}\n", audit.mismatched_gears.len());
+    for mismatched_gear in &audit.mismatched_gears {
+        output += &format!(
+            "  {} at ({}, {}) has {} part numbers\n",
+            mismatched_gear.part.value, mismatched_gear.part.x_loc, mismatched_gear.part.y_loc, mismatched_gear.neighbour_count
+        );
+    }
+
+    output += "Symbols:\n";
+    output += "  symbol count attached sum\n";
+    for breakdown in &audit.symbol_breakdowns {
+        output += &format!(
+            "  {:>6} {:>5} {:>8} {}\n",
+            breakdown.symbol, breakdown.count, breakdown.attached_part_numbers, breakdown.attached_sum
+        );
+    }
+
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_audit() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        let audit: Audit = audit_schematic(&contents, &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), &Adjacency::default()).unwrap();
+
+        let unattached: Vec<i64> = audit.unattached_part_numbers.iter().map(|part_number| part_number.value).collect();
+        assert_eq!(unattached, vec![114, 58]);
+        assert!(audit.isolated_parts.is_empty());
+        assert_eq!(
+            audit.mismatched_gears,
+            vec![MismatchedGear {
+                part: PartNode {
+                    value: '*',
+                    x_loc: 3,
+                    y_loc: 4,
+                },
+                neighbour_count: 1,
+            }]
+        );
+
+        let star: &SymbolBreakdown = &audit.symbol_breakdowns[0];
+        assert_eq!((star.symbol, star.count, star.attached_part_numbers), ('*', 3, 5));
+        assert_eq!(star.attached_sum, 467 + 35 + 617 + 755 + 598);
+        let total: i64 = audit.symbol_breakdowns.iter().map(|breakdown| breakdown.attached_sum).sum();
+        assert_eq!(total, 4361);
+
+        let overflowing: String = "9223372036854775807*1\n".to_string();
+        assert!(audit_schematic(&overflowing, &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), &Adjacency::default()).is_err());
+    }
+}
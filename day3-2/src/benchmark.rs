@@ -0,0 +1,146 @@
+use std::io;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::generator;
+#[cfg(feature = "parallel")]
+use crate::parallel;
+use crate::streaming;
+use crate::sum_gear_ratios;
+use crate::sum_part_numbers;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::GearRules;
+use crate::NumberFormat;
+use crate::Sums;
+
+use generator::GeneratedSchematic;
+use generator::GeneratorOptions;
+
+// Rows per chunk for the parallel solver, enough to keep each thread busy on a puzzle-sized schematic
+#[cfg(feature = "parallel")]
+const CHUNK_ROWS: usize = 16;
+
+// One solver's answers for a generated schematic, and how long it took
+pub struct SolverRun {
+    pub solver: &'static str,
+    pub sums: Sums,
+    pub elapsed: Duration,
+}
+
+impl SolverRun {
+    pub fn is_correct(&self, expected: &Sums) -> bool {
+        return self.sums == *expected;
+    }
+}
+
+pub struct BenchmarkResult {
+    pub seed: u64,
+    // The generator's own answers, worked out from where it put everything
+    pub expected: Sums,
+    pub runs: Vec<SolverRun>,
+}
+
+// Generates a schematic from the seed, and runs the indexed, streaming and (with the parallel feature) parallel
+// solvers over it under the puzzle's rules
+pub fn run_benchmark(seed: u64, options: &GeneratorOptions) -> Result<BenchmarkResult, String> {
+    let generated: GeneratedSchematic = generator::generate_schematic(seed, options)?;
+    let engine_schematic: &String = &generated.engine_schematic;
+    let mut runs: Vec<SolverRun> = Vec::new();
+
+    let start: Instant = Instant::now();
+    let part_number_sum: i64 = sum_part_numbers(engine_schematic, &ColumnModel::default(), &NumberFormat::default(), &Adjacency::default())?;
+    let gear_ratio_sum: i64 = sum_gear_ratios(
+        engine_schematic,
+        &ColumnModel::default(),
+        &NumberFormat::default(),
+        &GearRules::default(),
+        &Adjacency::default(),
+    )?;
+    runs.push(SolverRun {
+        solver: "indexed",
+        sums: Sums {
+            part_number_sum,
+            gear_ratio_sum,
+        },
+        elapsed: start.elapsed(),
+    });
+
+    let start: Instant = Instant::now();
+    let sums: Sums = streaming::solve_streaming(
+        &mut io::Cursor::new(engine_schematic.as_bytes()),
+        &ColumnModel::default(),
+        &NumberFormat::default(),
+        &GearRules::default(),
+        &mut |_| {},
+    )?;
+    runs.push(SolverRun {
+        solver: "streaming",
+        sums,
+        elapsed: start.elapsed(),
+    });
+
+    #[cfg(feature = "parallel")]
+    {
+        let start: Instant = Instant::now();
+        let sums: Sums = parallel::solve_parallel(engine_schematic, &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), CHUNK_ROWS)?;
+        runs.push(SolverRun {
+            solver: "parallel",
+            sums,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    return Ok(BenchmarkResult {
+        seed,
+        expected: Sums {
+            part_number_sum: generated.part_number_sum,
+            gear_ratio_sum: generated.gear_ratio_sum,
+        },
+        runs,
+    });
+}
+
+// One line per solver, marking any whose answers don't match the generator's
+pub fn format_benchmark(result: &BenchmarkResult) -> String {
+    let mut output: String = format!(
+        "seed {}: expected {} {}\n",
+        result.seed, result.expected.part_number_sum, result.expected.gear_ratio_sum
+    );
+    for run in &result.runs {
+        output += &format!(
+            "  {:<9} {} {} {:.3}ms {}\n",
+            run.solver,
+            run.sums.part_number_sum,
+            run.sums.gear_ratio_sum,
+            run.elapsed.as_secs_f64() * 1000.0,
+            if run.is_correct(&result.expected) { "ok" } else { "MISMATCH" }
+        );
+    }
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_run_benchmark() {
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 40,
+            height: 30,
+            ..GeneratorOptions::default()
+        };
+        for seed in 0..10 {
+            let result: BenchmarkResult = run_benchmark(seed, &options).unwrap();
+            let solvers: Vec<&str> = result.runs.iter().map(|run| run.solver).collect();
+            #[cfg(not(feature = "parallel"))]
+            assert_eq!(solvers, vec!["indexed", "streaming"]);
+            #[cfg(feature = "parallel")]
+            assert_eq!(solvers, vec!["indexed", "streaming", "parallel"]);
+            assert!(result.runs.iter().all(|run| run.is_correct(&result.expected)));
+            assert!(!format_benchmark(&result).contains("MISMATCH"));
+        }
+    }
+}
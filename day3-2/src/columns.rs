@@ -0,0 +1,107 @@
+use std::str::FromStr;
+
+use grid::Grid;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// What one column of the schematic is, which decides the x coordinates used for adjacency and rendering
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnModel {
+    // Each char is a column: the puzzle's own model
+    #[default]
+    Char,
+    // Each grapheme cluster is a column, so combining marks stay with the character they modify
+    Grapheme,
+    // Each grapheme cluster takes as many columns as it's displayed across, so wide (e.g. CJK or emoji)
+    // glyphs line up with what's on screen
+    DisplayWidth,
+}
+
+impl FromStr for ColumnModel {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<ColumnModel, String> {
+        return match description {
+            "char" => Ok(ColumnModel::Char),
+            "grapheme" => Ok(ColumnModel::Grapheme),
+            "width" => Ok(ColumnModel::DisplayWidth),
+            _ => Err(format!("Unknown column model {}, expected char, grapheme or width", description)),
+        };
+    }
+}
+
+// One column of a laid out row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Column {
+    // What the engine sees: the first char of the glyph, or '.' for the extra columns of a wide glyph
+    pub(crate) character: char,
+    // What's drawn: the whole glyph in its first column, and nothing in any extra columns it covers
+    pub(crate) glyph: String,
+}
+
+impl ColumnModel {
+    pub(crate) fn lay_out_row(&self, line: &str) -> Vec<Column> {
+        let glyphs: Vec<&str> = match self {
+            ColumnModel::Char => line.char_indices().map(|(i, character)| &line[i..i + character.len_utf8()]).collect(),
+            ColumnModel::Grapheme | ColumnModel::DisplayWidth => line.graphemes(true).collect(),
+        };
+
+        let mut columns: Vec<Column> = Vec::new();
+        for glyph in glyphs {
+            columns.push(Column {
+                character: glyph.chars().next().unwrap(),
+                glyph: glyph.to_string(),
+            });
+            // Zero width glyphs (e.g. control characters) still need a column to be seen in
+            if *self == ColumnModel::DisplayWidth {
+                for _ in 1..glyph.width() {
+                    columns.push(Column {
+                        character: '.',
+                        glyph: String::new(),
+                    });
+                }
+            }
+        }
+        return columns;
+    }
+
+    // The schematic as the engine's characters and the glyphs to draw, with short rows padded with blank cells
+    pub(crate) fn lay_out(&self, engine_schematic: &str) -> (Grid<char>, Grid<String>) {
+        let mut lines: Vec<&str> = engine_schematic.split("\n").collect();
+        if lines.last() == Some(&"") {
+            lines.pop();
+        }
+
+        let rows: Vec<Vec<Column>> = lines.iter().map(|line| self.lay_out_row(line)).collect();
+        let width: usize = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let column = |x: usize, y: usize| rows[y].get(x);
+
+        let schematic: Grid<char> = Grid::from_fn(width, rows.len(), |x, y| column(x, y).map_or('.', |column| column.character));
+        let glyphs: Grid<String> = Grid::from_fn(width, rows.len(), |x, y| {
+            column(x, y).map_or(".".to_string(), |column| column.glyph.clone())
+        });
+        return (schematic, glyphs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lay_out_row() {
+        // 'e' with a combining acute accent, then a wide CJK character
+        let line: &str = "1e\u{301}*\u{4e2d}2";
+        let characters = |column_model: ColumnModel| {
+            column_model.lay_out_row(line).iter().map(|column| column.character).collect::<String>()
+        };
+
+        assert_eq!(characters(ColumnModel::Char), "1e\u{301}*\u{4e2d}2");
+        assert_eq!(characters(ColumnModel::Grapheme), "1e*\u{4e2d}2");
+        assert_eq!(characters(ColumnModel::DisplayWidth), "1e*\u{4e2d}.2");
+        assert_eq!(ColumnModel::DisplayWidth.lay_out_row(line)[1].glyph, "e\u{301}");
+        assert_eq!(ColumnModel::DisplayWidth.lay_out_row(line)[4].glyph, "");
+        assert!("bytes".parse::<ColumnModel>().is_err());
+    }
+}
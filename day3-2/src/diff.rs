@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use crate::analyse_engine_schematic;
+use crate::escape::escape_json;
+use crate::graph::PartNode;
+use crate::graph::PartNumberNode;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::AnalysedSchematic;
+use crate::GearRules;
+use crate::NumberFormat;
+
+// The differences between two revisions of a schematic.
+// Part numbers are matched by value and position, so a part number whose value or span changed has disappeared and
+// a new one appeared in its place. Parts are matched by symbol and position.
+pub struct SchematicDiff {
+    pub appeared_part_numbers: Vec<PartNumberNode>,
+    pub disappeared_part_numbers: Vec<PartNumberNode>,
+    // Part numbers in both revisions that became attached or unattached
+    pub attachment_changes: Vec<AttachmentChange>,
+    // Parts in either revision whose gear ratio differs, including parts that are only in one revision
+    pub gear_changes: Vec<GearChange>,
+    // How much the part 1 and part 2 answers changed by, from the old revision to the new one
+    pub part_number_sum_delta: i128,
+    pub gear_ratio_sum_delta: i128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttachmentChange {
+    pub part_number: PartNumberNode,
+    pub is_attached: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GearChange {
+    pub part: PartNode,
+    pub old_ratio: Option<i64>,
+    pub new_ratio: Option<i64>,
+}
+
+pub enum DiffFormat {
+    Text,
+    Json,
+}
+
+// The answers and matching keys for one revision
+struct Revision {
+    part_numbers: Vec<(PartNumberNode, bool)>,
+    parts: Vec<(PartNode, Option<i64>)>,
+    part_number_sum: i128,
+    gear_ratio_sum: i128,
+}
+
+pub fn diff_schematics(
+    old_engine_schematic: &String,
+    new_engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+) -> Result<SchematicDiff, String> {
+    let old_revision: Revision = analyse_revision(old_engine_schematic, column_model, number_format, gear_rules, adjacency)?;
+    let new_revision: Revision = analyse_revision(new_engine_schematic, column_model, number_format, gear_rules, adjacency)?;
+
+    let old_part_numbers: HashMap<&PartNumberNode, bool> =
+        old_revision.part_numbers.iter().map(|(part_number, is_attached)| (part_number, *is_attached)).collect();
+    let new_part_numbers: HashMap<&PartNumberNode, bool> =
+        new_revision.part_numbers.iter().map(|(part_number, is_attached)| (part_number, *is_attached)).collect();
+
+    let mut appeared_part_numbers: Vec<PartNumberNode> = Vec::new();
+    let mut attachment_changes: Vec<AttachmentChange> = Vec::new();
+    for (part_number, is_attached) in &new_revision.part_numbers {
+        match old_part_numbers.get(part_number) {
+            None => appeared_part_numbers.push(part_number.clone()),
+            Some(was_attached) if was_attached != is_attached => attachment_changes.push(AttachmentChange {
+                part_number: part_number.clone(),
+                is_attached: *is_attached,
+            }),
+            Some(_) => {},
+        };
+    }
+    let disappeared_part_numbers: Vec<PartNumberNode> = old_revision
+        .part_numbers
+        .iter()
+        .filter(|(part_number, _)| !new_part_numbers.contains_key(part_number))
+        .map(|(part_number, _)| part_number.clone())
+        .collect();
+
+    let old_parts: HashMap<&PartNode, Option<i64>> = old_revision.parts.iter().map(|(part, ratio)| (part, *ratio)).collect();
+    let new_parts: HashMap<&PartNode, Option<i64>> = new_revision.parts.iter().map(|(part, ratio)| (part, *ratio)).collect();
+
+    let mut gear_changes: Vec<GearChange> = Vec::new();
+    for (part, old_ratio) in &old_revision.parts {
+        let new_ratio: Option<i64> = new_parts.get(part).copied().flatten();
+        if *old_ratio != new_ratio {
+            gear_changes.push(GearChange {
+                part: part.clone(),
+                old_ratio: *old_ratio,
+                new_ratio,
+            });
+        }
+    }
+    for (part, new_ratio) in &new_revision.parts {
+        if new_ratio.is_some() && !old_parts.contains_key(part) {
+            gear_changes.push(GearChange {
+                part: part.clone(),
+                old_ratio: None,
+                new_ratio: *new_ratio,
+            });
+        }
+    }
+    gear_changes.sort_by_key(|gear_change| (gear_change.part.y_loc, gear_change.part.x_loc));
+
+    return Ok(SchematicDiff {
+        appeared_part_numbers,
+        disappeared_part_numbers,
+        attachment_changes,
+        gear_changes,
+        part_number_sum_delta: new_revision.part_number_sum - old_revision.part_number_sum,
+        gear_ratio_sum_delta: new_revision.gear_ratio_sum - old_revision.gear_ratio_sum,
+    });
+}
+
+fn analyse_revision(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+) -> Result<Revision, String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+
+    let mut part_numbers: Vec<(PartNumberNode, bool)> = Vec::new();
+    let mut part_number_sum: i128 = 0;
+    for (part_number, is_attached) in analysed_schematic.part_numbers.iter().zip(&analysed_schematic.is_attached) {
+        if *is_attached {
+            part_number_sum += part_number.value as i128;
+        }
+        part_numbers.push((PartNumberNode::from(part_number), *is_attached));
+    }
+
+    let mut parts: Vec<(PartNode, Option<i64>)> = Vec::new();
+    let mut gear_ratio_sum: i128 = 0;
+    for (part_id, part) in analysed_schematic.parts.iter().enumerate() {
+        let gear_ratio: Option<i64> = analysed_schematic.gear_ratio(part_id, gear_rules)?;
+        gear_ratio_sum += gear_ratio.unwrap_or(0) as i128;
+        parts.push((PartNode::from(part), gear_ratio));
+    }
+
+    return Ok(Revision {
+        part_numbers,
+        parts,
+        part_number_sum,
+        gear_ratio_sum,
+    });
+}
+
+pub fn format_diff(diff: &SchematicDiff, format: &DiffFormat) -> String {
+    return match format {
+        DiffFormat::Text => format_diff_as_text(diff),
+        DiffFormat::Json => format_diff_as_json(diff),
+    };
+}
+
+fn format_diff_as_text(diff: &SchematicDiff) -> String {
+    let mut output: String = String::new();
+
+    for part_number in &diff.disappeared_part_numbers {
+        output += &format!("- {}\n", describe_part_number(part_number));
+    }
+    for part_number in &diff.appeared_part_numbers {
+        output += &format!("+ {}\n", describe_part_number(part_number));
+    }
+    for attachment_change in &diff.attachment_changes {
+        output += &format!(
+            "~ {} is now {}\n",
+            describe_part_number(&attachment_change.part_number),
+            if attachment_change.is_attached { "attached" } else { "unattached" }
+        );
+    }
+    for gear_change in &diff.gear_changes {
+        output += &format!(
+            "~ gear {} at ({}, {}): {} -> {}\n",
+            gear_change.part.value,
+            gear_change.part.x_loc,
+            gear_change.part.y_loc,
+            describe_ratio(gear_change.old_ratio),
+            describe_ratio(gear_change.new_ratio)
+        );
+    }
+    output += &format!("Part number sum: {:+}\n", diff.part_number_sum_delta);
+    output += &format!("Gear ratio sum: {:+}\n", diff.gear_ratio_sum_delta);
+
+    return output;
+}
+
+fn describe_part_number(part_number: &PartNumberNode) -> String {
+    return format!(
+        "{} at ({}..{}, {})",
+        part_number.value, part_number.x_start_loc, part_number.x_end_loc, part_number.y_loc
+    );
+}
+
+fn describe_ratio(ratio: Option<i64>) -> String {
+    return match ratio {
+        Some(ratio) => ratio.to_string(),
+        None => "none".to_string(),
+    };
+}
+
+fn format_diff_as_json(diff: &SchematicDiff) -> String {
+    let part_number_json = |part_number: &PartNumberNode| {
+        format!(
+            "{{\"value\": {}, \"x_start_loc\": {}, \"x_end_loc\": {}, \"y_loc\": {}}}",
+            part_number.value, part_number.x_start_loc, part_number.x_end_loc, part_number.y_loc
+        )
+    };
+    let ratio_json = |ratio: Option<i64>| match ratio {
+        Some(ratio) => ratio.to_string(),
+        None => "null".to_string(),
+    };
+
+    let appeared: Vec<String> = diff.appeared_part_numbers.iter().map(part_number_json).collect();
+    let disappeared: Vec<String> = diff.disappeared_part_numbers.iter().map(part_number_json).collect();
+    let attachment_changes: Vec<String> = diff
+        .attachment_changes
+        .iter()
+        .map(|attachment_change| {
+            format!(
+                "{{\"part_number\": {}, \"is_attached\": {}}}",
+                part_number_json(&attachment_change.part_number),
+                attachment_change.is_attached
+            )
+        })
+        .collect();
+    let gear_changes: Vec<String> = diff
+        .gear_changes
+        .iter()
+        .map(|gear_change| {
+            format!(
+                "{{\"symbol\": \"{}\", \"x_loc\": {}, \"y_loc\": {}, \"old_ratio\": {}, \"new_ratio\": {}}}",
+                escape_json(&gear_change.part.value.to_string()),
+                gear_change.part.x_loc,
+                gear_change.part.y_loc,
+                ratio_json(gear_change.old_ratio),
+                ratio_json(gear_change.new_ratio)
+            )
+        })
+        .collect();
+
+    return format!(
+        "{{\n  \"appeared_part_numbers\": [{}],\n  \"disappeared_part_numbers\": [{}],\n  \"attachment_changes\": [{}],\n  \"gear_changes\": [{}],\n  \"part_number_sum_delta\": {},\n  \"gear_ratio_sum_delta\": {}\n}}\n",
+        appeared.join(", "),
+        disappeared.join(", "),
+        attachment_changes.join(", "),
+        gear_changes.join(", "),
+        diff.part_number_sum_delta,
+        diff.gear_ratio_sum_delta
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::Aggregation;
+    use crate::NeighbourCount;
+    use std::fs;
+
+    #[test]
+    fn test_diff() {
+        let old_engine_schematic: String = fs::read_to_string("res/test_input.txt").unwrap();
+        // Move the gear at (3, 1) away from 467 and next to 114, and turn 58 into 59
+        let new_engine_schematic: String = old_engine_schematic.replacen("...*......", "....*.....", 1).replacen(".58.", ".59.", 1);
+        let diff: SchematicDiff =
+            diff_schematics(&old_engine_schematic, &new_engine_schematic, &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), &Adjacency::default()).unwrap();
+
+        let values = |part_numbers: &Vec<PartNumberNode>| part_numbers.iter().map(|part_number| part_number.value).collect::<Vec<i64>>();
+        assert_eq!(values(&diff.disappeared_part_numbers), vec![58]);
+        assert_eq!(values(&diff.appeared_part_numbers), vec![59]);
+
+        // 467 becomes unattached, 114 becomes attached
+        let attachment_changes: Vec<(i64, bool)> = diff
+            .attachment_changes
+            .iter()
+            .map(|attachment_change| (attachment_change.part_number.value, attachment_change.is_attached))
+            .collect();
+        assert_eq!(attachment_changes, vec![(467, false), (114, true)]);
+
+        let gear_changes: Vec<(usize, usize, Option<i64>, Option<i64>)> = diff
+            .gear_changes
+            .iter()
+            .map(|gear_change| (gear_change.part.x_loc, gear_change.part.y_loc, gear_change.old_ratio, gear_change.new_ratio))
+            .collect();
+        assert_eq!(gear_changes, vec![(3, 1, Some(467 * 35), None), (4, 1, None, Some(35 * 114))]);
+        assert_eq!(diff.part_number_sum_delta, 114 - 467);
+        assert_eq!(diff.gear_ratio_sum_delta, 35 * 114 - 467 * 35);
+
+        assert!(format_diff(&diff, &DiffFormat::Text).contains("Part number sum: -353\n"));
+        assert!(format_diff(&diff, &DiffFormat::Json).contains("\"old_ratio\": 16345, \"new_ratio\": null"));
+    }
+
+    #[test]
+    fn test_json_symbols() {
+        // JSON strings hold ' and non-ASCII characters as they are, where Rust's escape_default would escape them
+        let gear_rules: GearRules = GearRules {
+            symbols: vec!['\'', '\u{2500}', '"'],
+            neighbour_count: NeighbourCount::AtLeast(1),
+            aggregation: Aggregation::Sum,
+        };
+        let diff: SchematicDiff = diff_schematics(
+            &"1'.\n.\u{2500}.\n".to_string(),
+            &"1..\n..\u{2500}\n..\"2\n".to_string(),
+            &ColumnModel::default(),
+            &NumberFormat::default(),
+            &gear_rules,
+            &Adjacency::default(),
+        )
+        .unwrap();
+
+        let json: String = format_diff(&diff, &DiffFormat::Json);
+        assert!(json.contains("{\"symbol\": \"'\", \"x_loc\": 1, \"y_loc\": 0, \"old_ratio\": 1, \"new_ratio\": null}"));
+        assert!(json.contains("{\"symbol\": \"\u{2500}\", \"x_loc\": 2, \"y_loc\": 1, \"old_ratio\": null, \"new_ratio\": 2}"));
+        assert!(json.contains("{\"symbol\": \"\\\"\", \"x_loc\": 2, \"y_loc\": 2, \"old_ratio\": null, \"new_ratio\": 2}"));
+    }
+}
@@ -0,0 +1,62 @@
+// Escaping for the text formats the schematic is written out in. Any character other than a digit or '.' can be a
+// part, so symbols have to be escaped as well as free text.
+
+// For XML and HTML, in both content and attribute values.
+// XML 1.0 can't hold most C0 controls at all, even as character references, so those are replaced with the matching
+// Control Pictures symbol, e.g. U+0001 becomes U+2401.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped: String = String::new();
+    for character in text.chars() {
+        match character {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&#39;",
+            // Written as references so attribute values keep them
+            '\t' | '\n' | '\r' => escaped += &format!("&#{};", character as u32),
+            _ if (character as u32) < 0x20 => escaped.push(char::from_u32(0x2400 + character as u32).unwrap()),
+            _ => escaped.push(character),
+        };
+    }
+    return escaped;
+}
+
+// For the inside of a JSON string
+pub fn escape_json(text: &str) -> String {
+    let mut escaped: String = String::new();
+    for character in text.chars() {
+        match character {
+            '"' | '\\' => escaped += &format!("\\{}", character),
+            _ if character.is_control() => escaped += &format!("\\u{:04x}", character as u32),
+            _ => escaped.push(character),
+        };
+    }
+    return escaped;
+}
+
+// For the inside of a quoted DOT id
+pub fn escape_dot(text: &str) -> String {
+    let mut escaped: String = String::new();
+    for character in text.chars() {
+        match character {
+            '"' | '\\' => escaped += &format!("\\{}", character),
+            _ => escaped.push(character),
+        };
+    }
+    return escaped;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_xml("<a href=\"x\">'&'</a>"), "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;");
+        assert_eq!(escape_xml("\u{1}\t\u{1f}\u{7f}\u{2500}"), "\u{2401}&#9;\u{241f}\u{7f}\u{2500}");
+        assert_eq!(escape_json("\"\\\u{1}'\u{2500}"), "\\\"\\\\\\u0001'\u{2500}");
+        assert_eq!(escape_dot("\"\\*"), "\\\"\\\\*");
+    }
+}
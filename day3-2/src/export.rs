@@ -0,0 +1,236 @@
+use crate::analyse_engine_schematic;
+use crate::escape::escape_xml;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::AnalysedSchematic;
+use crate::GearRules;
+use crate::NumberFormat;
+
+// Size of one schematic cell in an SVG, in pixels
+const CELL_WIDTH: usize = 10;
+const CELL_HEIGHT: usize = 18;
+
+pub enum ExportFormat {
+    Svg,
+    Html,
+}
+
+// Fill colours for highlighted cells, matching the terminal renderer's
+const ATTACHED_COLOUR: &str = "#2e7d32";
+const UNATTACHED_COLOUR: &str = "#c62828";
+const GEAR_COLOUR: &str = "#f9a825";
+const ORPHAN_COLOUR: &str = "#8e24aa";
+const PART_COLOUR: &str = "#00838f";
+
+// Something highlighted in the export: a part number or a part, with its hover text
+struct Annotation {
+    x_start: usize,
+    x_end: usize,
+    y: usize,
+    colour: &'static str,
+    tooltip: String,
+}
+
+// Builds a standalone document for the schematic, where hovering over a part number or part
+// shows its value, coordinates, what it's adjacent to, and any gear ratios
+pub fn export_schematic(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+    format: &ExportFormat,
+) -> Result<String, String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+    let annotations: Vec<Annotation> = annotate(&analysed_schematic, gear_rules)?;
+
+    return Ok(match format {
+        ExportFormat::Svg => write_svg(&analysed_schematic, &annotations),
+        ExportFormat::Html => write_html(&analysed_schematic, &annotations),
+    });
+}
+
+fn annotate(analysed_schematic: &AnalysedSchematic, gear_rules: &GearRules) -> Result<Vec<Annotation>, String> {
+    let parts = &analysed_schematic.parts;
+    let part_numbers = &analysed_schematic.part_numbers;
+
+    let mut gear_ratios: Vec<Option<i64>> = Vec::new();
+    let mut adjacent_part_ids: Vec<Vec<usize>> = vec![Vec::new(); part_numbers.len()];
+    for part_id in 0..parts.len() {
+        gear_ratios.push(analysed_schematic.gear_ratio(part_id, gear_rules)?);
+        for part_number_id in &analysed_schematic.adjacent_part_number_ids[part_id] {
+            adjacent_part_ids[*part_number_id].push(part_id);
+        }
+    }
+
+    let describe_part = |part_id: usize| {
+        let part = &parts[part_id];
+        return match gear_ratios[part_id] {
+            Some(gear_ratio) => format!("{} at ({}, {}), gear ratio {}", part.value, part.x_loc, part.y_loc, gear_ratio),
+            None => format!("{} at ({}, {})", part.value, part.x_loc, part.y_loc),
+        };
+    };
+
+    let mut annotations: Vec<Annotation> = Vec::new();
+
+    for (part_number_id, part_number) in part_numbers.iter().enumerate() {
+        let mut tooltip: String = format!(
+            "{}\nx_start_loc {}, x_end_loc {}, y_loc {}",
+            part_number.value, part_number.x_start_loc, part_number.x_end_loc, part_number.y_loc
+        );
+        if adjacent_part_ids[part_number_id].is_empty() {
+            tooltip += "\nno adjacent parts";
+        }
+        for part_id in &adjacent_part_ids[part_number_id] {
+            tooltip += &format!("\nadjacent to {}", describe_part(*part_id));
+        }
+
+        annotations.push(Annotation {
+            x_start: part_number.x_start_loc,
+            x_end: part_number.x_end_loc,
+            y: part_number.y_loc,
+            colour: if analysed_schematic.is_attached[part_number_id] { ATTACHED_COLOUR } else { UNATTACHED_COLOUR },
+            tooltip,
+        });
+    }
+
+    for (part_id, part) in parts.iter().enumerate() {
+        let part_number_ids: &Vec<usize> = &analysed_schematic.adjacent_part_number_ids[part_id];
+        let values: Vec<String> = part_number_ids.iter().map(|id| part_numbers[*id].value.to_string()).collect();
+        let mut tooltip: String = describe_part(part_id);
+        if values.is_empty() {
+            tooltip += "\nno adjacent part numbers";
+        } else {
+            tooltip += &format!("\nadjacent to {}", values.join(", "));
+        }
+
+        let colour: &'static str = if gear_ratios[part_id].is_some() {
+            GEAR_COLOUR
+        } else if part_number_ids.is_empty() {
+            ORPHAN_COLOUR
+        } else {
+            PART_COLOUR
+        };
+
+        annotations.push(Annotation {
+            x_start: part.x_loc,
+            x_end: part.x_loc + part.width,
+            y: part.y_loc,
+            colour,
+            tooltip,
+        });
+    }
+
+    return Ok(annotations);
+}
+
+fn write_svg(analysed_schematic: &AnalysedSchematic, annotations: &Vec<Annotation>) -> String {
+    let schematic = &analysed_schematic.schematic;
+    let width: usize = schematic.width() * CELL_WIDTH;
+    let height: usize = schematic.height() * CELL_HEIGHT;
+
+    let mut output: String = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    output += "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n";
+
+    // Highlights go underneath the text, so the characters stay readable
+    for annotation in annotations {
+        output += &format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"0.35\"><title>{}</title></rect>\n",
+            annotation.x_start * CELL_WIDTH,
+            annotation.y * CELL_HEIGHT,
+            (annotation.x_end - annotation.x_start) * CELL_WIDTH,
+            CELL_HEIGHT,
+            annotation.colour,
+            escape_xml(&annotation.tooltip)
+        );
+    }
+
+    // Text ignores the pointer so hovering anywhere over a highlight shows its tooltip.
+    // textLength pins every character to its cell, whatever the font's own character width.
+    output += "<g font-family=\"monospace\" font-size=\"15\" pointer-events=\"none\">\n";
+    for (y, row) in analysed_schematic.glyphs.rows().enumerate() {
+        output += &format!(
+            "<text x=\"0\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacing\" xml:space=\"preserve\">{}</text>\n",
+            y * CELL_HEIGHT + CELL_HEIGHT * 3 / 4,
+            width,
+            escape_xml(&row.concat())
+        );
+    }
+    output += "</g>\n</svg>\n";
+
+    return output;
+}
+
+fn write_html(analysed_schematic: &AnalysedSchematic, annotations: &Vec<Annotation>) -> String {
+    let schematic = &analysed_schematic.schematic;
+
+    // Annotations never overlap, so each cell starts at most one highlighted span
+    let mut annotation_starting_at: Vec<Vec<Option<usize>>> = vec![vec![None; schematic.width()]; schematic.height()];
+    for (annotation_id, annotation) in annotations.iter().enumerate() {
+        annotation_starting_at[annotation.y][annotation.x_start] = Some(annotation_id);
+    }
+
+    let mut output: String = "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Engine schematic</title>\n".to_string();
+    output += "<style>\npre { font-family: monospace; line-height: 1.2; }\n";
+    output += "pre span { border-radius: 2px; cursor: help; }\npre span:hover { outline: 1px solid black; }\n</style>\n";
+    output += "</head>\n<body>\n<pre>\n";
+
+    for (y, row) in analysed_schematic.glyphs.rows().enumerate() {
+        let mut x: usize = 0;
+        while x < row.len() {
+            match annotation_starting_at[y][x] {
+                Some(annotation_id) => {
+                    let annotation: &Annotation = &annotations[annotation_id];
+                    output += &format!(
+                        "<span style=\"background-color: {}59\" title=\"{}\">{}</span>",
+                        annotation.colour,
+                        escape_xml(&annotation.tooltip),
+                        escape_xml(&row[annotation.x_start..annotation.x_end].concat())
+                    );
+                    x = annotation.x_end;
+                },
+                None => {
+                    output += &escape_xml(&row[x]);
+                    x += 1;
+                },
+            };
+        }
+        output += "\n";
+    }
+
+    output += "</pre>\n</body>\n</html>\n";
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const TEST_SCHEMATIC: &str = "467..114..\n...*......\n..35...&..\n";
+
+    #[test]
+    fn test_svg() {
+        let svg: String =
+            export_schematic(&TEST_SCHEMATIC.to_string(), &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), &Adjacency::default(), &ExportFormat::Svg).unwrap();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"54\""));
+        assert!(svg.contains("<title>467&#10;x_start_loc 0, x_end_loc 3, y_loc 0&#10;adjacent to * at (3, 1), gear ratio 16345</title>"));
+        assert!(svg.contains(">..35...&amp;..</text>"));
+    }
+
+    #[test]
+    fn test_html() {
+        let html: String =
+            export_schematic(&TEST_SCHEMATIC.to_string(), &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), &Adjacency::default(), &ExportFormat::Html).unwrap();
+        assert!(html.contains("title=\"114&#10;x_start_loc 5, x_end_loc 8, y_loc 0&#10;no adjacent parts\">114</span>..\n"));
+        assert!(html.contains("title=\"&amp; at (7, 2)&#10;no adjacent part numbers\">&amp;</span>"));
+
+        let html: String =
+            export_schematic(&"1\u{1}\n".to_string(), &ColumnModel::Char, &NumberFormat::default(), &GearRules::default(), &Adjacency::default(), &ExportFormat::Html).unwrap();
+        assert!(html.contains("title=\"\u{2401} at (1, 0)&#10;adjacent to 1\">\u{2401}</span>"));
+        assert!(!html.contains('\u{1}'));
+    }
+}
@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+// Decides which parts are gears, and how a gear's part numbers combine into its ratio
+#[derive(Clone)]
+pub struct GearRules {
+    // Parts with any of these symbols are gears, provided they have an allowed number of part numbers
+    pub symbols: Vec<char>,
+    pub neighbour_count: NeighbourCount,
+    pub aggregation: Aggregation,
+}
+
+// The puzzle's own rules: a '*' next to exactly two part numbers, with the ratio being their product
+impl Default for GearRules {
+    fn default() -> GearRules {
+        return GearRules {
+            symbols: vec!['*'],
+            neighbour_count: NeighbourCount::Exact(2),
+            aggregation: Aggregation::Product,
+        };
+    }
+}
+
+// How many adjacent part numbers a gear must have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NeighbourCount {
+    Exact(usize),
+    AtLeast(usize),
+    // Inclusive at both ends
+    Between(usize, usize),
+}
+
+impl NeighbourCount {
+    pub fn allows(&self, count: usize) -> bool {
+        return match self {
+            NeighbourCount::Exact(expected) => count == *expected,
+            NeighbourCount::AtLeast(minimum) => count >= *minimum,
+            NeighbourCount::Between(minimum, maximum) => (*minimum..=*maximum).contains(&count),
+        };
+    }
+}
+
+// Format: "N" for exactly N, "N+" for at least N, "N-M" for between N and M inclusive
+impl FromStr for NeighbourCount {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<NeighbourCount, String> {
+        let parse = |value: &str| {
+            return value
+                .parse::<usize>()
+                .map_err(|e| format!("Error parsing neighbour count {}: {}", description, e));
+        };
+
+        if let Some(minimum) = description.strip_suffix("+") {
+            return Ok(NeighbourCount::AtLeast(parse(minimum)?));
+        }
+        if let Some((minimum, maximum)) = description.split_once("-") {
+            let (minimum, maximum) = (parse(minimum)?, parse(maximum)?);
+            if minimum > maximum {
+                return Err(format!("Empty neighbour count range {}", description));
+            }
+            return Ok(NeighbourCount::Between(minimum, maximum));
+        }
+        return Ok(NeighbourCount::Exact(parse(description)?));
+    }
+}
+
+// How the values of a gear's part numbers combine into its ratio
+#[derive(Clone, Copy)]
+pub enum Aggregation {
+    Product,
+    Sum,
+    Max,
+    // Given the part number values in reading order; never called with an empty slice unless
+    // the neighbour count allows gears with no part numbers
+    Custom(fn(&[i64]) -> i64),
+}
+
+impl Aggregation {
+    // None if the ratio doesn't fit in an i64
+    pub fn apply(&self, values: &[i64]) -> Option<i64> {
+        return match self {
+            Aggregation::Product => values.iter().try_fold(1i64, |product, value| product.checked_mul(*value)),
+            Aggregation::Sum => values.iter().try_fold(0i64, |sum, value| sum.checked_add(*value)),
+            Aggregation::Max => Some(values.iter().copied().max().unwrap_or(0)),
+            Aggregation::Custom(aggregate) => Some(aggregate(values)),
+        };
+    }
+}
+
+// Custom aggregations can only be given through the library, so aren't parsed
+impl FromStr for Aggregation {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<Aggregation, String> {
+        return match description {
+            "product" => Ok(Aggregation::Product),
+            "sum" => Ok(Aggregation::Sum),
+            "max" => Ok(Aggregation::Max),
+            _ => Err(format!("Unknown aggregation {}, expected product, sum or max", description)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_neighbour_count() {
+        assert_eq!("2".parse::<NeighbourCount>().unwrap(), NeighbourCount::Exact(2));
+        assert_eq!("2+".parse::<NeighbourCount>().unwrap(), NeighbourCount::AtLeast(2));
+        assert_eq!("1-3".parse::<NeighbourCount>().unwrap(), NeighbourCount::Between(1, 3));
+        assert!("3-1".parse::<NeighbourCount>().is_err());
+        assert!("two".parse::<NeighbourCount>().is_err());
+
+        assert!(NeighbourCount::Between(1, 3).allows(3));
+        assert!(!NeighbourCount::Between(1, 3).allows(4));
+    }
+
+    #[test]
+    fn test_aggregation() {
+        assert_eq!(Aggregation::Product.apply(&[2, 3, 4]), Some(24));
+        assert_eq!(Aggregation::Sum.apply(&[2, 3, 4]), Some(9));
+        assert_eq!(Aggregation::Max.apply(&[2, 3, 4]), Some(4));
+        assert_eq!(Aggregation::Product.apply(&[i64::MAX, 2]), None);
+    }
+}
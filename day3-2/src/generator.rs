@@ -0,0 +1,233 @@
+// Builds random schematics from a seed, for stress testing and benchmarking the solvers.
+// The same seed and options always give the same schematic.
+pub struct GeneratorOptions {
+    pub width: usize,
+    pub height: usize,
+    // Chance of a number starting at any free cell
+    pub number_density: f64,
+    // Chance of a symbol at any free cell
+    pub symbol_density: f64,
+    // Symbols other than gears, which mustn't include digits or '.'
+    pub symbols: Vec<char>,
+    // Chance of a symbol being a '*' rather than one from symbols
+    pub gear_frequency: f64,
+}
+
+// Roughly the size and mix of a real puzzle input
+impl Default for GeneratorOptions {
+    fn default() -> GeneratorOptions {
+        return GeneratorOptions {
+            width: 140,
+            height: 140,
+            number_density: 0.1,
+            symbol_density: 0.05,
+            symbols: "#$%&+-/=@".chars().collect(),
+            gear_frequency: 0.3,
+        };
+    }
+}
+
+pub struct GeneratedSchematic {
+    pub engine_schematic: String,
+    // The answers under the puzzle's own rules, worked out from where the generator put everything
+    pub part_number_sum: i64,
+    pub gear_ratio_sum: i64,
+}
+
+// What the generator put in a row, kept so the answers don't depend on parsing the schematic back
+struct PlacedNumber {
+    value: i64,
+    x_start: usize,
+    // Exclusive
+    x_end: usize,
+}
+
+struct PlacedSymbol {
+    value: char,
+    x: usize,
+}
+
+pub fn generate_schematic(seed: u64, options: &GeneratorOptions) -> Result<GeneratedSchematic, String> {
+    for (name, density) in [("number_density", options.number_density), ("symbol_density", options.symbol_density), ("gear_frequency", options.gear_frequency)] {
+        if !(0.0..=1.0).contains(&density) {
+            return Err(format!("{} must be between 0 and 1, got {}", name, density));
+        }
+    }
+    if options.number_density + options.symbol_density > 1.0 {
+        return Err("number_density and symbol_density can't add up to more than 1".to_string());
+    }
+    if let Some(symbol) = options.symbols.iter().find(|symbol| symbol.is_ascii_digit() || **symbol == '.' || symbol.is_whitespace()) {
+        return Err(format!("Invalid symbol {:?}", symbol));
+    }
+    if options.symbols.is_empty() && options.gear_frequency < 1.0 && options.symbol_density > 0.0 {
+        return Err("No symbols to choose from when a symbol isn't a gear".to_string());
+    }
+
+    let mut seed: u64 = seed;
+    let mut engine_schematic: String = String::new();
+    let mut numbers: Vec<Vec<PlacedNumber>> = Vec::new();
+    let mut symbols: Vec<Vec<PlacedSymbol>> = Vec::new();
+
+    for _ in 0..options.height {
+        let mut row: Vec<char> = Vec::new();
+        let mut row_numbers: Vec<PlacedNumber> = Vec::new();
+        let mut row_symbols: Vec<PlacedSymbol> = Vec::new();
+
+        while row.len() < options.width {
+            let x: usize = row.len();
+            let roll: f64 = next_fraction(&mut seed);
+            // A number straight after another would run into it, so leave at least one cell between them
+            let follows_number: bool = row_numbers.last().is_some_and(|number| number.x_end == x);
+
+            if roll < options.number_density && !follows_number {
+                // One to three digits, without leading zeros, as in the puzzle
+                let digits: usize = (1 + (next_random(&mut seed) % 3) as usize).min(options.width - x);
+                let mut value: i64 = 1 + (next_random(&mut seed) % 9) as i64;
+                for _ in 1..digits {
+                    value = value * 10 + (next_random(&mut seed) % 10) as i64;
+                }
+                row.extend(value.to_string().chars());
+                row_numbers.push(PlacedNumber {
+                    value,
+                    x_start: x,
+                    x_end: x + digits,
+                });
+            } else if roll < options.number_density + options.symbol_density {
+                let value: char = if next_fraction(&mut seed) < options.gear_frequency {
+                    '*'
+                } else {
+                    options.symbols[(next_random(&mut seed) % options.symbols.len() as u64) as usize]
+                };
+                row.push(value);
+                row_symbols.push(PlacedSymbol { value, x });
+            } else {
+                row.push('.');
+            }
+        }
+
+        engine_schematic.extend(row);
+        engine_schematic.push('\n');
+        numbers.push(row_numbers);
+        symbols.push(row_symbols);
+    }
+
+    let (part_number_sum, gear_ratio_sum) = reference_answers(&numbers, &symbols);
+
+    return Ok(GeneratedSchematic {
+        engine_schematic,
+        part_number_sum,
+        gear_ratio_sum,
+    });
+}
+
+// Checks every symbol against every number in the rows above, below and its own, so it shares nothing with the
+// solvers' indexes
+fn reference_answers(numbers: &[Vec<PlacedNumber>], symbols: &[Vec<PlacedSymbol>]) -> (i64, i64) {
+    let mut is_attached: Vec<Vec<bool>> = numbers.iter().map(|row_numbers| vec![false; row_numbers.len()]).collect();
+    let mut gear_ratio_sum: i64 = 0;
+
+    for (y, row_symbols) in symbols.iter().enumerate() {
+        for symbol in row_symbols {
+            let mut adjacent_values: Vec<i64> = Vec::new();
+            for number_y in y.saturating_sub(1)..(y + 2).min(numbers.len()) {
+                for (number_id, number) in numbers[number_y].iter().enumerate() {
+                    if symbol.x + 1 >= number.x_start && symbol.x <= number.x_end {
+                        is_attached[number_y][number_id] = true;
+                        adjacent_values.push(number.value);
+                    }
+                }
+            }
+            if symbol.value == '*' && adjacent_values.len() == 2 {
+                gear_ratio_sum += adjacent_values[0] * adjacent_values[1];
+            }
+        }
+    }
+
+    let mut part_number_sum: i64 = 0;
+    for (row_numbers, row_is_attached) in numbers.iter().zip(&is_attached) {
+        for (number, is_attached) in row_numbers.iter().zip(row_is_attached) {
+            if *is_attached {
+                part_number_sum += number.value;
+            }
+        }
+    }
+
+    return (part_number_sum, gear_ratio_sum);
+}
+
+pub(crate) fn next_random(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    return *seed >> 33;
+}
+
+// Uniform in [0, 1)
+fn next_fraction(seed: &mut u64) -> f64 {
+    return next_random(seed) as f64 / (1u64 << 31) as f64;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::sum_gear_ratios;
+    use crate::sum_part_numbers;
+    use crate::Adjacency;
+    use crate::ColumnModel;
+    use crate::GearRules;
+    use crate::NumberFormat;
+
+    #[test]
+    fn test_generate_schematic() {
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 12,
+            height: 5,
+            ..GeneratorOptions::default()
+        };
+        let generated: GeneratedSchematic = generate_schematic(1, &options).unwrap();
+        assert_eq!(generated.engine_schematic.lines().count(), 5);
+        assert!(generated.engine_schematic.lines().all(|line| line.chars().count() == 12));
+        assert_eq!(generate_schematic(1, &options).unwrap().engine_schematic, generated.engine_schematic);
+
+        assert!(generate_schematic(1, &GeneratorOptions { number_density: 0.8, symbol_density: 0.3, ..GeneratorOptions::default() }).is_err());
+        assert!(generate_schematic(1, &GeneratorOptions { symbols: vec!['7'], ..GeneratorOptions::default() }).is_err());
+    }
+
+    #[test]
+    fn test_solvers_match_reference() {
+        let configurations: [GeneratorOptions; 3] = [
+            GeneratorOptions::default(),
+            // Crowded, so numbers regularly share gears and run into the edges
+            GeneratorOptions {
+                width: 30,
+                height: 30,
+                number_density: 0.4,
+                symbol_density: 0.3,
+                symbols: vec!['#'],
+                gear_frequency: 0.8,
+            },
+            GeneratorOptions {
+                width: 1,
+                height: 50,
+                number_density: 0.5,
+                symbol_density: 0.5,
+                symbols: Vec::new(),
+                gear_frequency: 1.0,
+            },
+        ];
+
+        for options in &configurations {
+            for seed in 0..20 {
+                let generated: GeneratedSchematic = generate_schematic(seed, options).unwrap();
+                let engine_schematic: &String = &generated.engine_schematic;
+                assert_eq!(
+                    sum_part_numbers(engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &Adjacency::default()).unwrap(),
+                    generated.part_number_sum
+                );
+                assert_eq!(
+                    sum_gear_ratios(engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default(), &Adjacency::default()).unwrap(),
+                    generated.gear_ratio_sum
+                );
+            }
+        }
+    }
+}
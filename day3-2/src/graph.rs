@@ -0,0 +1,387 @@
+use crate::analyse_engine_schematic;
+use crate::escape::escape_dot;
+use crate::escape::escape_json;
+use crate::escape::escape_xml;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::NumberFormat;
+use crate::AnalysedSchematic;
+use crate::EnginePart;
+use crate::PartNumber;
+
+// The bipartite graph of part numbers and parts, with an edge wherever a part number is adjacent to a part
+pub struct PartGraph {
+    part_numbers: Vec<PartNumberNode>,
+    parts: Vec<PartNode>,
+    // (part number id, part id) pairs, ordered by part then part number
+    edges: Vec<(usize, usize)>,
+    // For each part number and each part, the id of the connected component it belongs to
+    part_number_components: Vec<usize>,
+    part_components: Vec<usize>,
+    num_components: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PartNumberNode {
+    pub value: i64,
+    pub x_start_loc: usize,
+    pub x_end_loc: usize,
+    pub y_loc: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PartNode {
+    pub value: char,
+    pub x_loc: usize,
+    pub y_loc: usize,
+}
+
+impl From<&PartNumber> for PartNumberNode {
+    fn from(part_number: &PartNumber) -> PartNumberNode {
+        return PartNumberNode {
+            value: part_number.value,
+            x_start_loc: part_number.x_start_loc,
+            x_end_loc: part_number.x_end_loc,
+            y_loc: part_number.y_loc,
+        };
+    }
+}
+
+impl From<&EnginePart> for PartNode {
+    fn from(part: &EnginePart) -> PartNode {
+        return PartNode {
+            value: part.value,
+            x_loc: part.x_loc,
+            y_loc: part.y_loc,
+        };
+    }
+}
+
+// A maximal set of part numbers and parts linked to each other through shared adjacencies
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Component {
+    pub part_number_ids: Vec<usize>,
+    pub part_ids: Vec<usize>,
+}
+
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+    Json,
+}
+
+impl PartGraph {
+    pub fn build(engine_schematic: &String, column_model: &ColumnModel, number_format: &NumberFormat, adjacency: &Adjacency) -> Result<PartGraph, String> {
+        let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+
+        let part_numbers: Vec<PartNumberNode> = analysed_schematic
+            .part_numbers
+            .iter()
+            .map(PartNumberNode::from)
+            .collect();
+        let parts: Vec<PartNode> = analysed_schematic
+            .parts
+            .iter()
+            .map(PartNode::from)
+            .collect();
+
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (part_id, part_number_ids) in analysed_schematic.adjacent_part_number_ids.iter().enumerate() {
+            for part_number_id in part_number_ids {
+                edges.push((*part_number_id, part_id));
+            }
+        }
+
+        // Union-find over every node, with part numbers first and parts after them
+        let mut parents: Vec<usize> = (0..part_numbers.len() + parts.len()).collect();
+        for (part_number_id, part_id) in &edges {
+            let part_number_root: usize = find_root(&mut parents, *part_number_id);
+            let part_root: usize = find_root(&mut parents, part_numbers.len() + part_id);
+            parents[part_root] = part_number_root;
+        }
+
+        // Number the components in order of their first node
+        let mut component_for_root: Vec<Option<usize>> = vec![None; parents.len()];
+        let mut components: Vec<usize> = Vec::new();
+        let mut num_components: usize = 0;
+        for node in 0..parents.len() {
+            let root: usize = find_root(&mut parents, node);
+            let component: usize = match component_for_root[root] {
+                Some(component) => component,
+                None => {
+                    component_for_root[root] = Some(num_components);
+                    num_components += 1;
+                    num_components - 1
+                },
+            };
+            components.push(component);
+        }
+        let part_components: Vec<usize> = components.split_off(part_numbers.len());
+
+        return Ok(PartGraph {
+            part_numbers,
+            parts,
+            edges,
+            part_number_components: components,
+            part_components,
+            num_components,
+        });
+    }
+
+    pub fn part_numbers(&self) -> &Vec<PartNumberNode> {
+        return &self.part_numbers;
+    }
+
+    pub fn parts(&self) -> &Vec<PartNode> {
+        return &self.parts;
+    }
+
+    pub fn edges(&self) -> &Vec<(usize, usize)> {
+        return &self.edges;
+    }
+
+    pub fn parts_adjacent_to(&self, part_number_id: usize) -> Vec<usize> {
+        return self
+            .edges
+            .iter()
+            .filter(|(edge_part_number_id, _)| *edge_part_number_id == part_number_id)
+            .map(|(_, part_id)| *part_id)
+            .collect();
+    }
+
+    pub fn part_numbers_adjacent_to(&self, part_id: usize) -> Vec<usize> {
+        return self
+            .edges
+            .iter()
+            .filter(|(_, edge_part_id)| *edge_part_id == part_id)
+            .map(|(part_number_id, _)| *part_number_id)
+            .collect();
+    }
+
+    // Every connected component, including single unattached part numbers and orphan parts
+    pub fn connected_components(&self) -> Vec<Component> {
+        let mut components: Vec<Component> = vec![
+            Component {
+                part_number_ids: Vec::new(),
+                part_ids: Vec::new(),
+            };
+            self.num_components
+        ];
+        for (part_number_id, component) in self.part_number_components.iter().enumerate() {
+            components[*component].part_number_ids.push(part_number_id);
+        }
+        for (part_id, component) in self.part_components.iter().enumerate() {
+            components[*component].part_ids.push(part_id);
+        }
+        return components;
+    }
+
+    // Clusters of more than one part number linked through shared parts
+    pub fn part_number_clusters(&self) -> Vec<Component> {
+        return self
+            .connected_components()
+            .into_iter()
+            .filter(|component| component.part_number_ids.len() > 1)
+            .collect();
+    }
+
+    pub fn component_of_part_number(&self, part_number_id: usize) -> usize {
+        return self.part_number_components[part_number_id];
+    }
+
+    pub fn component_of_part(&self, part_id: usize) -> usize {
+        return self.part_components[part_id];
+    }
+
+    pub fn export(&self, format: &GraphFormat) -> String {
+        return match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Json => self.to_json(),
+        };
+    }
+
+    // Part numbers are nodes "n0", "n1", ... and parts are "p0", "p1", ... in every format
+    pub fn to_dot(&self) -> String {
+        let mut output: String = "graph schematic {\n".to_string();
+        for (part_number_id, part_number) in self.part_numbers.iter().enumerate() {
+            output += &format!(
+                "  n{} [label=\"{}\", shape=box, x_start_loc={}, x_end_loc={}, y_loc={}, component={}];\n",
+                part_number_id,
+                part_number.value,
+                part_number.x_start_loc,
+                part_number.x_end_loc,
+                part_number.y_loc,
+                self.part_number_components[part_number_id]
+            );
+        }
+        for (part_id, part) in self.parts.iter().enumerate() {
+            output += &format!(
+                "  p{} [label=\"{}\", shape=circle, x_loc={}, y_loc={}, component={}];\n",
+                part_id,
+                escape_dot(&part.value.to_string()),
+                part.x_loc,
+                part.y_loc,
+                self.part_components[part_id]
+            );
+        }
+        for (part_number_id, part_id) in &self.edges {
+            output += &format!("  n{} -- p{};\n", part_number_id, part_id);
+        }
+        output += "}\n";
+        return output;
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut output: String = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+        output += "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n";
+        for (id, kind) in [
+            ("kind", "string"),
+            ("value", "string"),
+            ("x_start_loc", "int"),
+            ("x_end_loc", "int"),
+            ("x_loc", "int"),
+            ("y_loc", "int"),
+            ("component", "int"),
+        ] {
+            output += &format!("  <key id=\"{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>\n", id, id, kind);
+        }
+        output += "  <graph id=\"schematic\" edgedefault=\"undirected\">\n";
+        for (part_number_id, part_number) in self.part_numbers.iter().enumerate() {
+            output += &format!(
+                "    <node id=\"n{}\"><data key=\"kind\">part_number</data><data key=\"value\">{}</data><data key=\"x_start_loc\">{}</data><data key=\"x_end_loc\">{}</data><data key=\"y_loc\">{}</data><data key=\"component\">{}</data></node>\n",
+                part_number_id,
+                part_number.value,
+                part_number.x_start_loc,
+                part_number.x_end_loc,
+                part_number.y_loc,
+                self.part_number_components[part_number_id]
+            );
+        }
+        for (part_id, part) in self.parts.iter().enumerate() {
+            output += &format!(
+                "    <node id=\"p{}\"><data key=\"kind\">part</data><data key=\"value\">{}</data><data key=\"x_loc\">{}</data><data key=\"y_loc\">{}</data><data key=\"component\">{}</data></node>\n",
+                part_id,
+                escape_xml(&part.value.to_string()),
+                part.x_loc,
+                part.y_loc,
+                self.part_components[part_id]
+            );
+        }
+        for (part_number_id, part_id) in &self.edges {
+            output += &format!("    <edge source=\"n{}\" target=\"p{}\"/>\n", part_number_id, part_id);
+        }
+        output += "  </graph>\n</graphml>\n";
+        return output;
+    }
+
+    pub fn to_json(&self) -> String {
+        let part_numbers: Vec<String> = self
+            .part_numbers
+            .iter()
+            .enumerate()
+            .map(|(part_number_id, part_number)| {
+                format!(
+                    "    {{\"id\": \"n{}\", \"value\": {}, \"x_start_loc\": {}, \"x_end_loc\": {}, \"y_loc\": {}, \"component\": {}}}",
+                    part_number_id,
+                    part_number.value,
+                    part_number.x_start_loc,
+                    part_number.x_end_loc,
+                    part_number.y_loc,
+                    self.part_number_components[part_number_id]
+                )
+            })
+            .collect();
+        let parts: Vec<String> = self
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(part_id, part)| {
+                format!(
+                    "    {{\"id\": \"p{}\", \"value\": \"{}\", \"x_loc\": {}, \"y_loc\": {}, \"component\": {}}}",
+                    part_id,
+                    escape_json(&part.value.to_string()),
+                    part.x_loc,
+                    part.y_loc,
+                    self.part_components[part_id]
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|(part_number_id, part_id)| format!("    {{\"source\": \"n{}\", \"target\": \"p{}\"}}", part_number_id, part_id))
+            .collect();
+
+        return format!(
+            "{{\n  \"part_numbers\": [\n{}\n  ],\n  \"parts\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}\n",
+            part_numbers.join(",\n"),
+            parts.join(",\n"),
+            edges.join(",\n")
+        );
+    }
+}
+
+fn find_root(parents: &mut [usize], node: usize) -> usize {
+    let mut root: usize = node;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    // Point everything on the way straight at the root, so later lookups are quick
+    let mut current: usize = node;
+    while parents[current] != root {
+        let next: usize = parents[current];
+        parents[current] = root;
+        current = next;
+    }
+    return root;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_components() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        let graph: PartGraph = PartGraph::build(&contents, &ColumnModel::default(), &NumberFormat::default(), &Adjacency::default()).unwrap();
+
+        assert_eq!(graph.part_numbers().len(), 10);
+        assert_eq!(graph.parts().len(), 6);
+        assert_eq!(graph.edges().len(), 8);
+        assert_eq!(graph.parts_adjacent_to(0), vec![0]);
+        assert_eq!(graph.part_numbers_adjacent_to(0), vec![0, 2]);
+
+        // 114 and 58 are unattached, so each forms its own component
+        assert_eq!(graph.connected_components().len(), 8);
+        let clusters: Vec<Component> = graph.part_number_clusters();
+        let values: Vec<Vec<i64>> = clusters
+            .iter()
+            .map(|cluster| cluster.part_number_ids.iter().map(|id| graph.part_numbers()[*id].value).collect())
+            .collect();
+        assert_eq!(values, vec![vec![467, 35], vec![755, 598]]);
+        assert_eq!(graph.component_of_part_number(0), graph.component_of_part(0));
+    }
+
+    #[test]
+    fn test_export() {
+        let graph: PartGraph = PartGraph::build(&"12*\n..\"".to_string(), &ColumnModel::default(), &NumberFormat::default(), &Adjacency::default()).unwrap();
+        assert_eq!(
+            graph.to_dot(),
+            "graph schematic {\n  n0 [label=\"12\", shape=box, x_start_loc=0, x_end_loc=2, y_loc=0, component=0];\n  p0 [label=\"*\", shape=circle, x_loc=2, y_loc=0, component=0];\n  p1 [label=\"\\\"\", shape=circle, x_loc=2, y_loc=1, component=0];\n  n0 -- p0;\n  n0 -- p1;\n}\n"
+        );
+        assert!(graph.to_graphml().contains("<edge source=\"n0\" target=\"p1\"/>"));
+        assert!(graph.to_json().contains("{\"id\": \"p1\", \"value\": \"\\\"\", \"x_loc\": 2, \"y_loc\": 1, \"component\": 0}"));
+
+        // Quotes and control characters are parts too, and GraphML can't hold the control character as it is
+        let graph: PartGraph = PartGraph::build(&"1\u{1}'\"\n".to_string(), &ColumnModel::default(), &NumberFormat::default(), &Adjacency::default()).unwrap();
+        let graphml: String = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"value\">\u{2401}</data>"));
+        assert!(graphml.contains("<data key=\"value\">&#39;</data>"));
+        assert!(graphml.contains("<data key=\"value\">&quot;</data>"));
+        assert!(!graphml.contains('\u{1}'));
+        assert!(graph.to_json().contains("\"value\": \"\\u0001\""));
+    }
+}
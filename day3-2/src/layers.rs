@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use grid::Grid;
+
+use crate::adjacent_part_number_ids;
+use crate::build_part_number_index;
+use crate::extend_part_widths;
+use crate::parse_engine_schematic;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::EnginePart;
+use crate::GearRules;
+use crate::NumberFormat;
+use crate::PartNumber;
+
+// A schematic in any number of dimensions, made of 2D layers separated by blank lines. Counting x and y as the first
+// two dimensions, a run of N blank lines steps one along dimension N + 2 and starts again from 0 along every
+// dimension between it and y. So one blank line between layers gives a 3D stack of layers, and two blank lines start
+// the next stack along the fourth dimension.
+// A part number and a part are adjacent if they're adjacent within a layer as in the puzzle, or on layers within one
+// step of each other along every dimension past y and within one step along x and y, which is the puzzle's 8
+// surrounding cells extended to 26 in 3D, 80 in 4D, etc.
+// Both answers keep their puzzle meaning: a part number counts if any part is adjacent to it on any layer, and a gear
+// is judged by the gear rules against all its adjacent part numbers across every neighbouring layer.
+struct LayeredSchematic {
+    dimensions: usize,
+    // In reading order, with every coordinate after y in layer_locs
+    parts: Vec<EnginePart>,
+    part_numbers: Vec<PartNumber>,
+    // Each layer's part number index, by the layer's layer_locs
+    part_number_indexes: HashMap<Vec<usize>, Grid<Option<usize>>>,
+}
+
+// The part 1 answer for a layered schematic
+pub fn sum_layered_part_numbers(engine_schematic: &String, column_model: &ColumnModel, number_format: &NumberFormat) -> Result<i64, String> {
+    let schematic: LayeredSchematic = parse_layered_schematic(engine_schematic, column_model, number_format)?;
+
+    let mut is_attached: Vec<bool> = vec![false; schematic.part_numbers.len()];
+    for part_number_id in adjacent_layered_part_number_ids(&schematic).iter().flatten() {
+        is_attached[*part_number_id] = true;
+    }
+
+    let mut sum: i64 = 0;
+    for (part_number, is_attached) in schematic.part_numbers.iter().zip(is_attached) {
+        if is_attached {
+            sum = match sum.checked_add(part_number.value) {
+                Some(sum) => sum,
+                None => {
+                    return Err("Sum of part numbers overflowed".to_string());
+                },
+            };
+        }
+    }
+
+    return Ok(sum);
+}
+
+// The part 2 answer for a layered schematic, where a gear's part numbers can be on any neighbouring layer
+pub fn sum_layered_gear_ratios(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+) -> Result<i64, String> {
+    let schematic: LayeredSchematic = parse_layered_schematic(engine_schematic, column_model, number_format)?;
+
+    let mut sum: i64 = 0;
+    for (part, part_number_ids) in schematic.parts.iter().zip(adjacent_layered_part_number_ids(&schematic)) {
+        if !gear_rules.symbols.contains(&part.value) || !gear_rules.neighbour_count.allows(part_number_ids.len()) {
+            continue;
+        }
+
+        let values: Vec<i64> = part_number_ids.iter().map(|id| schematic.part_numbers[*id].value).collect();
+        let gear_ratio: i64 = match gear_rules.aggregation.apply(&values) {
+            Some(gear_ratio) => gear_ratio,
+            None => {
+                return Err(format!("Gear ratio overflowed for gear at ({}, {}) on layer {:?}", part.x_loc, part.y_loc, part.layer_locs));
+            },
+        };
+        sum = match sum.checked_add(gear_ratio) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of gear ratios overflowed".to_string());
+            },
+        };
+    }
+
+    return Ok(sum);
+}
+
+fn parse_layered_schematic(engine_schematic: &String, column_model: &ColumnModel, number_format: &NumberFormat) -> Result<LayeredSchematic, String> {
+    let mut lines: Vec<&str> = engine_schematic.split("\n").map(|line| line.strip_suffix("\r").unwrap_or(line)).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+
+    // First split the schematic into layers and find where each is, so the number of dimensions is known
+    let mut layers: Vec<(Vec<&str>, Vec<usize>)> = Vec::new();
+    let mut layer_locs: Vec<usize> = Vec::new();
+    let mut blank_lines: usize = 0;
+    for line in lines {
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+
+        if layers.is_empty() {
+            layers.push((Vec::new(), layer_locs.clone()));
+        } else if blank_lines > 0 {
+            // Step along the dimension the run of blank lines stands for, and start again along every lower one
+            if layer_locs.len() < blank_lines {
+                layer_locs.resize(blank_lines, 0);
+            }
+            layer_locs[blank_lines - 1] += 1;
+            layer_locs[..blank_lines - 1].fill(0);
+            layers.push((Vec::new(), layer_locs.clone()));
+        }
+        blank_lines = 0;
+        layers.last_mut().unwrap().0.push(line);
+    }
+
+    let dimensions: usize = layer_locs.len() + 2;
+    let mut parts: Vec<EnginePart> = Vec::new();
+    let mut part_numbers: Vec<PartNumber> = Vec::new();
+    let mut part_number_indexes: HashMap<Vec<usize>, Grid<Option<usize>>> = HashMap::new();
+
+    // Each layer is read as a 2D schematic of its own
+    for (layer_lines, mut layer_locs) in layers {
+        layer_locs.resize(dimensions - 2, 0);
+        let (layer, glyphs) = column_model.lay_out(&layer_lines.join("\n"));
+        let (mut layer_parts, mut layer_part_numbers) = parse_engine_schematic(&layer, number_format)?;
+        extend_part_widths(&mut layer_parts, &glyphs);
+
+        // The index refers to part numbers by their position in the whole schematic
+        let part_number_id_offset: usize = part_numbers.len();
+        let part_number_index: Grid<Option<usize>> = build_part_number_index(&layer, &layer_part_numbers)
+            .map(|part_number_id| part_number_id.map(|part_number_id| part_number_id_offset + part_number_id));
+        part_number_indexes.insert(layer_locs.clone(), part_number_index);
+
+        for part in &mut layer_parts {
+            part.layer_locs = layer_locs.clone();
+        }
+        for part_number in &mut layer_part_numbers {
+            part_number.layer_locs = layer_locs.clone();
+        }
+        parts.extend(layer_parts);
+        part_numbers.extend(layer_part_numbers);
+    }
+
+    return Ok(LayeredSchematic {
+        dimensions,
+        parts,
+        part_numbers,
+        part_number_indexes,
+    });
+}
+
+// For each part, the indices of its adjacent part numbers in reading order, found by looking the part up in the index
+// of its own layer and of every neighbouring layer
+fn adjacent_layered_part_number_ids(schematic: &LayeredSchematic) -> Vec<Vec<usize>> {
+    // Every combination of -1, 0 and 1 along each dimension past y, including all zeros for the part's own layer
+    let mut offsets: Vec<Vec<isize>> = vec![Vec::new()];
+    for _ in 2..schematic.dimensions {
+        offsets = offsets.iter().flat_map(|offset| [-1, 0, 1].map(|step| [offset.clone(), vec![step]].concat())).collect();
+    }
+
+    let mut adjacent_part_number_ids_by_part: Vec<Vec<usize>> = Vec::new();
+    for part in &schematic.parts {
+        let mut part_number_ids: Vec<usize> = Vec::new();
+        for offset in &offsets {
+            let layer_locs: Option<Vec<usize>> = part.layer_locs.iter().zip(offset).map(|(loc, step)| loc.checked_add_signed(*step)).collect();
+            let part_number_index: &Grid<Option<usize>> = match layer_locs.and_then(|layer_locs| schematic.part_number_indexes.get(&layer_locs)) {
+                Some(part_number_index) => part_number_index,
+                None => continue,
+            };
+
+            part_number_ids.extend(adjacent_part_number_ids(part_number_index, part, &Adjacency::default()));
+            // On another layer, the cells directly above or below the part are adjacent too
+            if offset.iter().any(|step| *step != 0) {
+                for x in part.x_loc..part.x_loc + part.width {
+                    if let Some(Some(part_number_id)) = part_number_index.get(x, part.y_loc) {
+                        part_number_ids.push(*part_number_id);
+                    }
+                }
+            }
+        }
+        part_number_ids.sort();
+        part_number_ids.dedup();
+        adjacent_part_number_ids_by_part.push(part_number_ids);
+    }
+
+    return adjacent_part_number_ids_by_part;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::generator;
+    use crate::is_part_number_for_part;
+    use generator::GeneratorOptions;
+    use std::fs;
+
+    #[test]
+    fn test_single_layer() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        assert_eq!(sum_layered_part_numbers(&contents, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 4361);
+        assert_eq!(sum_layered_gear_ratios(&contents, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 467835);
+    }
+
+    #[test]
+    fn test_3d_layers() {
+        // 12 is on the layer below the gear, 3 the layer above, and 7 is two layers away
+        let engine_schematic: String = "12.\n...\n\n.*.\n...\n\n..3\n...\n\n...\n7..\n".to_string();
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 15);
+        assert_eq!(sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 36);
+
+        // With both 4 and 3 on the layer above, the gear has three part numbers, so it's no longer a gear
+        let engine_schematic: String = "12.\n...\n\n.*.\n...\n\n4.3\n...\n".to_string();
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 19);
+        assert_eq!(sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 0);
+
+        // A wide part reaches the number under its second column on the next layer
+        let engine_schematic: String = "\u{4e2d}..\n\n..5\n".to_string();
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::DisplayWidth, &NumberFormat::default()).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_4d_layers() {
+        // Two blank lines step along the fourth dimension, back to the first layer
+        let engine_schematic: String = "5..\n\n...\n\n\n.*.\n\n.6.\n".to_string();
+        let schematic: LayeredSchematic = parse_layered_schematic(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap();
+        assert_eq!(schematic.dimensions, 4);
+        let part_number_locs: Vec<(usize, usize, Vec<usize>)> =
+            schematic.part_numbers.iter().map(|part_number| (part_number.x_start_loc, part_number.y_loc, part_number.layer_locs.clone())).collect();
+        assert_eq!(part_number_locs, vec![(0, 0, vec![0, 0]), (1, 0, vec![1, 1])]);
+        assert_eq!(schematic.parts[0].layer_locs, vec![0, 1]);
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 11);
+        // The gear's part numbers are one step away along the fourth dimension, on either side of it along the third
+        assert_eq!(sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 30);
+
+        // 8 is two steps away along the fourth dimension, so it isn't a part number for the gear
+        let engine_schematic: String = "5..\n\n\n.*.\n\n.6.\n\n\n...\n\n\n.8.\n".to_string();
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 11);
+        assert_eq!(sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 30);
+
+        // A third part number diagonally away along both the third and fourth dimensions spoils the gear
+        let engine_schematic: String = "5..\n\n...\n\n\n.*.\n\n.6.\n\n\n...\n\n..2\n".to_string();
+        assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), 13);
+        assert_eq!(sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_indexed_matches_comparison() {
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 8,
+            height: 6,
+            number_density: 0.25,
+            symbol_density: 0.1,
+            symbols: "#%".chars().collect(),
+            gear_frequency: 0.5,
+        };
+        // Stacks of 6 layers in 3D, as 2 x 3 layers in 4D, and as 2 x 1 x 3 layers in 5D
+        for separators in [["\n"; 5], ["\n", "\n", "\n\n", "\n", "\n"], ["\n", "\n", "\n\n\n", "\n", "\n"]] {
+            for seed in 0..20 {
+                let mut engine_schematic: String = String::new();
+                for layer in 0..6 {
+                    if layer > 0 {
+                        engine_schematic += separators[layer as usize - 1];
+                    }
+                    engine_schematic += &generator::generate_schematic(seed * 6 + layer, &options).unwrap().engine_schematic;
+                }
+
+                // Compare every part number against every part
+                let schematic: LayeredSchematic = parse_layered_schematic(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap();
+                let is_adjacent = |part_number: &PartNumber, part: &EnginePart| {
+                    is_part_number_for_part(part_number, part, usize::MAX, usize::MAX, &Adjacency::default())
+                };
+
+                let mut expected_part_number_sum: i64 = 0;
+                for part_number in &schematic.part_numbers {
+                    if schematic.parts.iter().any(|part| is_adjacent(part_number, part)) {
+                        expected_part_number_sum += part_number.value;
+                    }
+                }
+                assert_eq!(sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(), expected_part_number_sum);
+
+                let mut expected_gear_ratio_sum: i64 = 0;
+                for part in schematic.parts.iter().filter(|part| part.value == '*') {
+                    let values: Vec<i64> = schematic.part_numbers.iter().filter(|part_number| is_adjacent(part_number, part)).map(|part_number| part_number.value).collect();
+                    if values.len() == 2 {
+                        expected_gear_ratio_sum += values[0] * values[1];
+                    }
+                }
+                assert_eq!(
+                    sum_layered_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default()).unwrap(),
+                    expected_gear_ratio_sum
+                );
+            }
+        }
+
+        // With one layer, the answers match the 2D engine's
+        let engine_schematic: String = "..1*2...\n3.......\n.....#.4\n".to_string();
+        assert_eq!(
+            sum_layered_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default()).unwrap(),
+            crate::sum_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &Adjacency::default()).unwrap()
+        );
+    }
+}
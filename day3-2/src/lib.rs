@@ -0,0 +1,499 @@
+use grid::Grid;
+
+pub mod audit;
+pub mod benchmark;
+pub mod columns;
+pub mod diff;
+pub mod escape;
+pub mod export;
+pub mod gear_rules;
+pub mod generator;
+pub mod graph;
+pub mod layers;
+pub mod neighbourhood;
+pub mod number_format;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod render;
+pub mod schematic;
+pub mod streaming;
+pub mod validation;
+
+pub use columns::ColumnModel;
+pub use gear_rules::Aggregation;
+pub use gear_rules::GearRules;
+pub use gear_rules::NeighbourCount;
+pub use neighbourhood::Adjacency;
+pub use neighbourhood::Neighbourhood;
+pub use number_format::NumberFormat;
+
+pub fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<i64, String> {
+    return sum_gear_ratios(
+        engine_schematic,
+        &ColumnModel::default(),
+        &NumberFormat::default(),
+        &GearRules::default(),
+        &Adjacency::default(),
+    );
+}
+
+// The part 1 answer: the sum of every part number adjacent to at least one part
+pub fn sum_part_numbers(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    adjacency: &Adjacency,
+) -> Result<i64, String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+
+    // Sum the associated part numbers
+    let mut sum: i64 = 0;
+    for (part_number_id, part_number) in analysed_schematic.part_numbers.iter().enumerate() {
+        if analysed_schematic.is_attached[part_number_id] {
+            sum = match sum.checked_add(part_number.value) {
+                Some(sum) => sum,
+                None => {
+                    return Err("Sum of part numbers overflowed".to_string());
+                },
+            };
+        }
+    }
+
+    return Ok(sum);
+}
+
+pub fn sum_gear_ratios(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+) -> Result<i64, String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+
+    // For each gear, calculate the gear ratio and add to sum
+    let mut sum: i64 = 0;
+    for part_id in 0..analysed_schematic.parts.len() {
+        if let Some(gear_ratio) = analysed_schematic.gear_ratio(part_id, gear_rules)? {
+            sum = match sum.checked_add(gear_ratio) {
+                Some(sum) => sum,
+                None => {
+                    return Err("Sum of gear ratios overflowed".to_string());
+                },
+            };
+        }
+    }
+
+    return Ok(sum);
+}
+
+// A parsed schematic, along with which part numbers each part is adjacent to
+struct AnalysedSchematic {
+    schematic: Grid<char>,
+    // What to draw for each cell, which differs from the schematic for anything but char columns
+    glyphs: Grid<String>,
+    parts: Vec<EnginePart>,
+    part_numbers: Vec<PartNumber>,
+    // For each part, the indices of its adjacent part numbers in reading order
+    adjacent_part_number_ids: Vec<Vec<usize>>,
+    // For each part number, whether it's adjacent to any part
+    is_attached: Vec<bool>,
+}
+
+impl AnalysedSchematic {
+    // The ratio of the part at part_id, or None if the gear rules don't make it a gear
+    fn gear_ratio(&self, part_id: usize, gear_rules: &GearRules) -> Result<Option<i64>, String> {
+        let part: &EnginePart = &self.parts[part_id];
+        let part_number_ids: &Vec<usize> = &self.adjacent_part_number_ids[part_id];
+        if !gear_rules.symbols.contains(&part.value) || !gear_rules.neighbour_count.allows(part_number_ids.len()) {
+            return Ok(None);
+        }
+
+        let values: Vec<i64> = part_number_ids.iter().map(|id| self.part_numbers[*id].value).collect();
+        return match gear_rules.aggregation.apply(&values) {
+            Some(gear_ratio) => Ok(Some(gear_ratio)),
+            None => Err(format!("Gear ratio overflowed for gear at ({}, {})", part.x_loc, part.y_loc)),
+        };
+    }
+}
+
+fn analyse_engine_schematic(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    adjacency: &Adjacency,
+) -> Result<AnalysedSchematic, String> {
+    // Short rows are padded with blank cells, which can't hold parts or part numbers
+    let (schematic, glyphs) = column_model.lay_out(engine_schematic);
+    let (mut parts, part_numbers) = parse_engine_schematic(&schematic, number_format)?;
+
+    extend_part_widths(&mut parts, &glyphs);
+
+    // For each part, find and mark every part number touching it
+    let part_number_index: Grid<Option<usize>> = build_part_number_index(&schematic, &part_numbers);
+    let mut adjacent_part_number_ids_by_part: Vec<Vec<usize>> = Vec::new();
+    let mut is_attached: Vec<bool> = vec![false; part_numbers.len()];
+
+    for part in &parts {
+        let mut part_number_ids: Vec<usize> = adjacent_part_number_ids(&part_number_index, part, adjacency);
+        part_number_ids.sort();
+        for part_number_id in &part_number_ids {
+            is_attached[*part_number_id] = true;
+        }
+        adjacent_part_number_ids_by_part.push(part_number_ids);
+    }
+
+    return Ok(AnalysedSchematic {
+        schematic,
+        glyphs,
+        parts,
+        part_numbers,
+        adjacent_part_number_ids: adjacent_part_number_ids_by_part,
+        is_attached,
+    });
+}
+
+// A wide glyph covers the columns after it, and is adjacent to whatever any of them are
+fn extend_part_widths(parts: &mut [EnginePart], glyphs: &Grid<String>) {
+    for part in parts {
+        while part.x_loc + part.width < glyphs.width() && glyphs[(part.x_loc + part.width, part.y_loc)].is_empty() {
+            part.width += 1;
+        }
+    }
+}
+
+fn parse_engine_schematic(schematic: &Grid<char>, number_format: &NumberFormat) -> Result<(Vec<EnginePart>, Vec<PartNumber>), String> {
+    // Parse through the lines of the schematic
+    let mut parts: Vec<EnginePart> = Vec::new();
+    let mut part_numbers: Vec<PartNumber> = Vec::new();
+
+    for (y, row) in schematic.rows().enumerate() {
+        // Anything not covered by a part number (including a sign that doesn't start one) may be a part
+        let row_part_numbers: Vec<PartNumber> = tokenize_row(row, y, 0, number_format)?;
+        let mut is_part_number: Vec<bool> = vec![false; row.len()];
+        for part_number in &row_part_numbers {
+            is_part_number[part_number.x_start_loc..part_number.x_end_loc].fill(true);
+        }
+
+        for (x, character) in row.iter().copied().enumerate() {
+            if !is_part_number[x] && is_engine_part(character) {
+                parts.push(EnginePart {
+                    value: character,
+                    x_loc: x,
+                    y_loc: y,
+                    layer_locs: Vec::new(),
+                    width: 1,
+                });
+            }
+        }
+        part_numbers.extend(row_part_numbers);
+    }
+
+    return Ok((parts, part_numbers));
+}
+
+// The part numbers in a run of cells from row y, where the run starts at x_offset.
+// Numbers are read greedily from left to right, so the run mustn't start or end partway through a number.
+fn tokenize_row(cells: &[char], y: usize, x_offset: usize, number_format: &NumberFormat) -> Result<Vec<PartNumber>, String> {
+    let mut part_numbers: Vec<PartNumber> = Vec::new();
+
+    let mut x: usize = 0;
+    while x < cells.len() {
+        match number_format.read_number(cells, x)? {
+            Some((value, x_end)) => {
+                part_numbers.push(PartNumber {
+                    value,
+                    x_start_loc: x_offset + x,
+                    x_end_loc: x_offset + x_end,
+                    y_loc: y,
+                    layer_locs: Vec::new(),
+                });
+                x = x_end;
+            },
+            None => x += 1,
+        };
+    }
+
+    return Ok(part_numbers);
+}
+
+// Reference implementations of the part number and gear ratio sums, comparing every part number against every part.
+// Kept to check the indexed implementations against.
+#[cfg(test)]
+fn sum_part_numbers_by_comparison(schematic: &Grid<char>, adjacency: &Adjacency) -> i64 {
+    let (parts, part_numbers) = parse_engine_schematic(schematic, &NumberFormat::default()).unwrap();
+
+    let mut sum: i64 = 0;
+    for part_number in &part_numbers {
+        if parts.iter().any(|part| is_part_number_for_part(part_number, part, schematic.width(), schematic.height(), adjacency)) {
+            sum += part_number.value;
+        }
+    }
+
+    return sum;
+}
+
+#[cfg(test)]
+fn sum_gear_ratios_by_comparison(schematic: &Grid<char>, adjacency: &Adjacency) -> i64 {
+    let (parts, part_numbers) = parse_engine_schematic(schematic, &NumberFormat::default()).unwrap();
+
+    // For each gear, calculate the gear ratio and add to sum
+    let mut sum: i64 = 0;
+
+    for part in parts {
+        if part.value != '*' {
+            continue;
+        }
+
+        let mut associated_part_numbers: Vec<i64> = Vec::new();
+
+        for part_number in &part_numbers {
+            if is_part_number_for_part(&part_number, &part, schematic.width(), schematic.height(), adjacency) {
+                associated_part_numbers.push(part_number.value);
+            }    
+        }
+
+        if associated_part_numbers.iter().count() == 2 {
+            let gear_ratio: i64 = associated_part_numbers.get(0).unwrap() * associated_part_numbers.get(1).unwrap(); 
+            sum += gear_ratio;
+        }
+    }
+
+    return sum;
+}
+
+fn is_digit(character: char) -> bool {
+    return character >= '0' && character <= '9';
+}
+
+fn is_engine_part(character: char) -> bool {
+    return !is_digit(character) && character != '.';
+}
+
+// Maps each cell of the schematic to the index of the part number covering it, if any,
+// so the part numbers around a part can be looked up without checking every part number.
+fn build_part_number_index(schematic: &Grid<char>, part_numbers: &Vec<PartNumber>) -> Grid<Option<usize>> {
+    let mut part_number_index: Grid<Option<usize>> = Grid::new(schematic.width(), schematic.height(), None);
+
+    for (part_number_id, part_number) in part_numbers.iter().enumerate() {
+        for x in part_number.x_start_loc..part_number.x_end_loc {
+            part_number_index[(x, part_number.y_loc)] = Some(part_number_id);
+        }
+    }
+
+    return part_number_index;
+}
+
+// The indices of the part numbers adjacent to the part, each listed once
+fn adjacent_part_number_ids(part_number_index: &Grid<Option<usize>>, part: &EnginePart, adjacency: &Adjacency) -> Vec<usize> {
+    let mut part_number_ids: Vec<usize> = Vec::new();
+
+    for part_x in part.x_loc..part.x_loc + part.width {
+        for (x, y) in adjacency.neighbours(part_x, part.y_loc, part_number_index.width(), part_number_index.height()) {
+            if let Some(part_number_id) = part_number_index[(x, y)] {
+                if !part_number_ids.contains(&part_number_id) {
+                    part_number_ids.push(part_number_id);
+                }
+            }
+        }
+    }
+
+    return part_number_ids;
+}
+
+#[cfg(test)]
+fn is_part_number_for_part(part_number: &PartNumber, part: &EnginePart, width: usize, height: usize, adjacency: &Adjacency) -> bool {
+    // In a layered schematic, the layers must be within one step of each other along every dimension past y
+    let layer_locs: Vec<(&usize, &usize)> = part_number.layer_locs.iter().zip(&part.layer_locs).collect();
+    if layer_locs.iter().any(|(loc, part_loc)| loc.abs_diff(**part_loc) > 1) {
+        return false;
+    }
+    let is_same_layer: bool = layer_locs.iter().all(|(loc, part_loc)| loc == part_loc);
+
+    // A part number is associated to a part if any part of it is adjacent to the part.
+    // On a different layer, that includes the cells directly above or below the part.
+    for x in part_number.x_start_loc..part_number.x_end_loc {
+        for part_x in part.x_loc..part.x_loc + part.width {
+            let is_stacked: bool = !is_same_layer && (x, part_number.y_loc) == (part_x, part.y_loc);
+            if is_stacked || adjacency.is_adjacent((x, part_number.y_loc), (part_x, part.y_loc), width, height) {
+                return true;
+            }
+        }
+    }
+
+    return false;
+}
+
+// An engine part will be a non-numeric, non-period symbol that isn't part of a part number, and an associated x- and y-coordinate.
+struct EnginePart {
+    value: char,
+    x_loc: usize,
+    y_loc: usize,
+    // Any coordinates after y, in a layered schematic: the layer, then any further dimensions. Empty in 2D.
+    layer_locs: Vec<usize>,
+    // How many columns the part's glyph covers, from x_loc
+    width: usize,
+}
+
+// Both answers at once, from the solvers that work them out together
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sums {
+    pub part_number_sum: i64,
+    pub gear_ratio_sum: i64,
+}
+
+// A part number can span across multiple x values, but only one y value, and only one layer
+struct PartNumber {
+    value: i64,
+    x_start_loc: usize,
+    x_end_loc: usize,
+    y_loc: usize,
+    // As for EnginePart
+    layer_locs: Vec<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use grid::Grid;
+    use crate::generator;
+    use crate::Adjacency;
+    use crate::Aggregation;
+    use crate::ColumnModel;
+    use crate::GearRules;
+    use crate::NeighbourCount;
+    use crate::Neighbourhood;
+    use crate::NumberFormat;
+    use generator::GeneratorOptions;
+
+    #[test]
+    fn test() {
+        // Read input file
+        let input_file: String = "res/test_input.txt".to_string();
+        let contents: String = match fs::read_to_string(input_file) {
+            Ok(f) => f,
+            Err(e) => panic!("{}", e),
+        };
+
+        let result = match crate::sum_part_numbers_from_engine_schematic(&contents) {
+            Ok(res) => res,
+            Err(message) => panic!("{}", message),
+        };
+        assert_eq!(result, 467835);
+        let sum: i64 = crate::sum_part_numbers(&contents, &ColumnModel::default(), &NumberFormat::default(), &Adjacency::default()).unwrap();
+        assert_eq!(sum, 4361);
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let engine_schematic: String = "467..114..\n...*......\n..35..633.\n......#...\n617*......\n".to_string();
+
+        let gear_rules: GearRules = GearRules {
+            symbols: vec!['*', '#'],
+            neighbour_count: NeighbourCount::AtLeast(1),
+            aggregation: Aggregation::Sum,
+        };
+        let sum_gear_ratios = |gear_rules: &GearRules| {
+            crate::sum_gear_ratios(&engine_schematic, &ColumnModel::default(), &NumberFormat::default(), gear_rules, &Adjacency::default())
+        };
+        assert_eq!(sum_gear_ratios(&gear_rules).unwrap(), 467 + 35 + 633 + 617);
+
+        let gear_rules: GearRules = GearRules {
+            symbols: vec!['*'],
+            neighbour_count: NeighbourCount::Between(1, 1),
+            aggregation: Aggregation::Custom(|values| values[0] * 10),
+        };
+        assert_eq!(sum_gear_ratios(&gear_rules).unwrap(), 6170);
+    }
+
+    #[test]
+    fn test_number_format() {
+        let engine_schematic: String = "-5*3.0x1F\n....-.....\n1_000.+2..\n".to_string();
+        let column_model: ColumnModel = ColumnModel::default();
+        let adjacency: Adjacency = Adjacency::default();
+
+        // By default the signs, 'x' and '_' are all parts, splitting up the numbers around them (including two zeros)
+        let decimal: NumberFormat = NumberFormat::default();
+        assert_eq!(crate::sum_part_numbers(&engine_schematic, &column_model, &decimal, &adjacency).unwrap(), 5 + 3 + 1 + 1 + 2);
+        assert_eq!(
+            crate::sum_gear_ratios(&engine_schematic, &column_model, &decimal, &GearRules::default(), &adjacency).unwrap(),
+            15
+        );
+
+        // The '-' on the second row doesn't start a number, so is still a part, and +2 no longer touches any part
+        let number_format: NumberFormat = "signed,hex,underscores".parse().unwrap();
+        assert_eq!(
+            crate::sum_part_numbers(&engine_schematic, &column_model, &number_format, &adjacency).unwrap(),
+            -5 + 3 + 31 + 1000
+        );
+        assert_eq!(
+            crate::sum_gear_ratios(&engine_schematic, &column_model, &number_format, &GearRules::default(), &adjacency).unwrap(),
+            -15
+        );
+    }
+
+    #[test]
+    fn test_column_model() {
+        let sum = |engine_schematic: &str, column_model: ColumnModel| {
+            return crate::sum_part_numbers(
+                &engine_schematic.to_string(),
+                &column_model,
+                &NumberFormat::default(),
+                &Adjacency::default(),
+            )
+            .unwrap();
+        };
+
+        // As chars, the combining accent is a part of its own, next to the 7
+        assert_eq!(sum("*\u{301}..\n..7\n", ColumnModel::Char), 7);
+        assert_eq!(sum("*\u{301}..\n..7\n", ColumnModel::Grapheme), 0);
+
+        // The wide character covers the first two columns, so reaches the 5 diagonally
+        assert_eq!(sum("\u{4e2d}..\n..5\n", ColumnModel::Grapheme), 0);
+        assert_eq!(sum("\u{4e2d}..\n..5\n", ColumnModel::DisplayWidth), 5);
+    }
+
+    #[test]
+    fn test_indexed_matches_comparison() {
+        let adjacencies: Vec<Adjacency> = vec![
+            Adjacency::default(),
+            Adjacency {
+                neighbourhood: Neighbourhood::VonNeumann,
+                toroidal: false,
+            },
+            Adjacency {
+                neighbourhood: Neighbourhood::Chebyshev(2),
+                toroidal: true,
+            },
+            Adjacency {
+                neighbourhood: Neighbourhood::Manhattan(3),
+                toroidal: true,
+            },
+        ];
+
+        // Generated schematics, dense enough that gears regularly have one, two or more part numbers
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 20,
+            height: 20,
+            number_density: 0.4,
+            symbol_density: 0.3,
+            symbols: Vec::new(),
+            gear_frequency: 1.0,
+        };
+        for seed in 0..50 {
+            let engine_schematic: String = generator::generate_schematic(seed, &options).unwrap().engine_schematic;
+            let schematic: Grid<char> = Grid::parse(&engine_schematic).unwrap();
+            for adjacency in &adjacencies {
+                assert_eq!(
+                    crate::sum_part_numbers(&engine_schematic, &ColumnModel::default(), &NumberFormat::default(), adjacency).unwrap(),
+                    crate::sum_part_numbers_by_comparison(&schematic, adjacency)
+                );
+                assert_eq!(
+                    crate::sum_gear_ratios(&engine_schematic, &ColumnModel::default(), &NumberFormat::default(), &GearRules::default(), adjacency).unwrap(),
+                    crate::sum_gear_ratios_by_comparison(&schematic, adjacency)
+                );
+            }
+        }
+    }
+}
@@ -1,17 +1,96 @@
 use std::env;
 use std::fs;
+use std::io;
+use std::fs::File;
+
+use day3_2::audit;
+use day3_2::benchmark;
+use day3_2::diff;
+use day3_2::export;
+use day3_2::generator;
+use day3_2::graph;
+use day3_2::layers;
+#[cfg(feature = "parallel")]
+use day3_2::parallel;
+use day3_2::render;
+use day3_2::streaming;
+use day3_2::validation;
+use day3_2::sum_gear_ratios;
+use day3_2::Adjacency;
+use day3_2::Aggregation;
+use day3_2::ColumnModel;
+use day3_2::GearRules;
+use day3_2::NeighbourCount;
+use day3_2::Neighbourhood;
+use day3_2::NumberFormat;
+
+use diff::DiffFormat;
+use export::ExportFormat;
+use generator::GeneratorOptions;
+use graph::GraphFormat;
+use graph::PartGraph;
+use render::RenderOptions;
+use validation::CharacterSet;
 
 fn main() {
     // Check command line arguments
     let argc: Vec<String> = env::args().collect();
     let argv: usize = argc.len();
     if argv < 2 {
-        println!("Usage: input_file num_red num_green num_blue");
+        println!("Usage: input_file [--gear-symbols symbols] [--neighbours (N|N+|N-M)] [--aggregate (product|sum|max)]");
+        println!("                  [--neighbourhood (moore|von-neumann|chebyshev:K|manhattan:K)] [--toroidal]");
+        println!("                  [--numbers (decimal|signed,hex,underscores)] [--columns (char|grapheme|width)]");
+        println!("                  [--validate] [--blanks characters] [--symbols characters]");
+        println!("                  [--render [--no-colour] | --export (svg|html) | --graph (dot|graphml|json) | --audit");
+        println!("                   | --diff new_input_file [--diff-format (text|json)] | --layers | --stream]");
+        #[cfg(feature = "parallel")]
+        println!("                  [--parallel chunk_rows]");
+        println!("       --generate seed WxH [count]");
+        println!("With --layers, the schematic is a stack of layers separated by blank lines. A run of N blank lines steps");
+        println!("to the next layer along dimension N + 2, counting x and y as dimensions 1 and 2.");
         return;
     }
 
-    // Read input file
+    // Generated schematics replace the input file
+    if argc[1] == "--generate" {
+        run_generated(&argc[2..]);
+        return;
+    }
+
+    let Options {
+        column_model,
+        number_format,
+        gear_rules,
+        adjacency,
+        character_set,
+        output_format,
+    } = match parse_options(&argc[2..]) {
+        Err(e) => {
+            eprintln!("Error reading options: {}", e);
+            return;
+        }
+        Ok(result) => result,
+    };
+
     let input_file: &String = &argc[1];
+
+    // Streaming reads the input file a row at a time, so never load the whole thing
+    if let OutputFormat::Stream = output_format {
+        let file: File = match File::open(input_file) {
+            Err(e) => {
+                eprintln!("Error reading input_file {}: {}", input_file, e);
+                return;
+            }
+            Ok(f) => f,
+        };
+        match streaming::solve_streaming(&mut io::BufReader::new(file), &column_model, &number_format, &gear_rules, &mut |_| {}) {
+            Ok(sums) => println!("{}", sums.gear_ratio_sum),
+            Err(e) => eprintln!("Error calculating sum of part numbers: {}", e),
+        };
+        return;
+    }
+
+    // Read input file
     let contents: String = match fs::read_to_string(input_file) {
         Err(e) => {
             eprintln!("Error reading input_file {}: {}", input_file, e);
@@ -20,7 +99,92 @@ fn main() {
         Ok(f) => f,
     };
 
-    let result: usize = match sum_part_numbers_from_engine_schematic(&contents) {
+    // Validation rewrites any configured blanks, so carry on with the validated schematic
+    let contents: String = match character_set {
+        Some(character_set) => match validation::validate_schematic(&contents, &column_model, &character_set) {
+            Ok(result) => result,
+            Err(violations) => {
+                for violation in &violations {
+                    println!("{}", violation);
+                }
+                println!("{} violations", violations.len());
+                return;
+            }
+        },
+        None => contents,
+    };
+
+    match output_format {
+        OutputFormat::Render(render_options) => {
+            let mut output = io::BufWriter::new(io::stdout().lock());
+            let result: Result<(), String> = render::render_schematic(
+                &mut output,
+                &contents,
+                &column_model,
+                &number_format,
+                &gear_rules,
+                &adjacency,
+                &render_options,
+            );
+            if let Err(e) = result {
+                eprintln!("Error rendering schematic: {}", e);
+            }
+            return;
+        }
+        OutputFormat::Export(export_format) => {
+            match export::export_schematic(&contents, &column_model, &number_format, &gear_rules, &adjacency, &export_format) {
+                Ok(result) => print!("{}", result),
+                Err(e) => eprintln!("Error exporting schematic: {}", e),
+            };
+            return;
+        }
+        OutputFormat::Graph(graph_format) => {
+            match PartGraph::build(&contents, &column_model, &number_format, &adjacency) {
+                Ok(part_graph) => print!("{}", part_graph.export(&graph_format)),
+                Err(e) => eprintln!("Error building part graph: {}", e),
+            };
+            return;
+        }
+        OutputFormat::Audit => {
+            match audit::audit_schematic(&contents, &column_model, &number_format, &gear_rules, &adjacency) {
+                Ok(result) => print!("{}", audit::format_audit(&result)),
+                Err(e) => eprintln!("Error auditing schematic: {}", e),
+            };
+            return;
+        }
+        OutputFormat::Diff(new_input_file, diff_format) => {
+            let new_contents: String = match fs::read_to_string(&new_input_file) {
+                Err(e) => {
+                    eprintln!("Error reading new_input_file {}: {}", new_input_file, e);
+                    return;
+                }
+                Ok(f) => f,
+            };
+            match diff::diff_schematics(&contents, &new_contents, &column_model, &number_format, &gear_rules, &adjacency) {
+                Ok(result) => print!("{}", diff::format_diff(&result, &diff_format)),
+                Err(e) => eprintln!("Error comparing schematics: {}", e),
+            };
+            return;
+        }
+        OutputFormat::Layers => {
+            match layers::sum_layered_gear_ratios(&contents, &column_model, &number_format, &gear_rules) {
+                Ok(result) => println!("{}", result),
+                Err(e) => eprintln!("Error calculating sum of part numbers across layers: {}", e),
+            };
+            return;
+        }
+        #[cfg(feature = "parallel")]
+        OutputFormat::Parallel(chunk_rows) => {
+            match parallel::solve_parallel(&contents, &column_model, &number_format, &gear_rules, chunk_rows) {
+                Ok(sums) => println!("{}", sums.gear_ratio_sum),
+                Err(e) => eprintln!("Error calculating sum of part numbers: {}", e),
+            };
+            return;
+        }
+        OutputFormat::Answer | OutputFormat::Stream => {}
+    };
+
+    let result: i64 = match sum_gear_ratios(&contents, &column_model, &number_format, &gear_rules, &adjacency) {
         Ok(result) => result,
         Err(message) => {
             eprintln!("Error calculating sum of part numbers: {}", message);
@@ -31,173 +195,225 @@ fn main() {
     println!("{}", result);
 }
 
-fn sum_part_numbers_from_engine_schematic(engine_schematic: &String) -> Result<usize, String> {
-    // Parse through the lines of the schematic
-    let mut parts: Vec<EnginePart> = Vec::new();
-    let mut part_numbers: Vec<PartNumber> = Vec::new();
-
-    let mut parsing_state: Option<PartNumberParsingState> = None;
-
-    for (y, line) in engine_schematic.split("\n").enumerate() {
-        for (x, character) in line.chars().enumerate() {
-            // If we've just stopped parsing a character, parse the result and commit.
-            if !is_digit(character) && parsing_state.is_some() {
-                match parsing_state {
-                    Some(existing) => {
-                        match from_parsing_state(&existing, x, y) {
-                            Ok(result) => part_numbers.push(result),
-                            Err(e) => return Err(e),
-                        }
-                    }
-                    None => {}
-                };
-                parsing_state = None;
-            }
-
-            if is_engine_part(character) {
-                parts.push(EnginePart {
-                    value: character,
-                    x_loc: x,
-                    y_loc: y,
-                });
-            } else if is_digit(character) {
-                match parsing_state {
-                    Some(ref mut existing) => existing.part_number_digits.push(character),
-                    None => {
-                        parsing_state = Some(PartNumberParsingState {
-                            parsing_part_number_x_start: x,
-                            part_number_digits: vec![character],
-                        })
-                    }
-                };
-            }
-        }
-
-        // If we're still parsing a part number at the end of the line, parse the result and commit.
-        if parsing_state.is_some() {
-            match parsing_state {
-                Some(existing) => match from_parsing_state(&existing, line.chars().count(), y) {
-                    Ok(result) => part_numbers.push(result),
-                    Err(e) => return Err(e),
-                },
-                None => {}
-            };
-            parsing_state = None;
-        };
+// Checks every solver against the generator's answers for count schematics, from seed onwards
+fn run_generated(args: &[String]) {
+    if args.len() < 2 || args.len() > 3 {
+        println!("Usage: --generate seed WxH [count]");
+        return;
     }
 
-    // For each gear, calculate the gear ratio and add to sum
-    let mut sum: usize = 0;
-
-    for part in parts {
-        if part.value != '*' {
-            continue;
+    let seed: u64 = match args[0].parse::<u64>() {
+        Err(e) => {
+            eprintln!("Error parsing seed {}: {}", args[0], e);
+            return;
         }
+        Ok(result) => result,
+    };
 
-        let mut associated_part_numbers: Vec<usize> = Vec::new();
-
-        for part_number in &part_numbers {
-            if is_part_number_for_part(&part_number, &part) {
-                associated_part_numbers.push(part_number.value);
-            }    
+    let (width, height) = match args[1].split_once("x").map(|(width, height)| (width.parse::<usize>(), height.parse::<usize>())) {
+        Some((Ok(width), Ok(height))) => (width, height),
+        _ => {
+            eprintln!("Error parsing size {}, expected WxH", args[1]);
+            return;
         }
+    };
 
-        if associated_part_numbers.iter().count() == 2 {
-            let gear_ratio: usize = associated_part_numbers.get(0).unwrap() * associated_part_numbers.get(1).unwrap(); 
-            sum += gear_ratio;
+    let count: u64 = match args.get(2).map(|count| count.parse::<u64>()) {
+        None => 1,
+        Some(Ok(count)) => count,
+        Some(Err(e)) => {
+            eprintln!("Error parsing count {}: {}", args[2], e);
+            return;
         }
-    }
-
-    return Ok(sum);
-}
-
-fn from_parsing_state(
-    parsing_state: &PartNumberParsingState,
-    current_x: usize,
-    current_y: usize,
-) -> Result<PartNumber, String> {
-    let value = match format!(
-        "{}",
-        parsing_state.part_number_digits.iter().collect::<String>()
-    )
-    .parse::<usize>()
-    {
-        Ok(value) => value,
-        Err(e) => return Err(format!("Error parsing part number: {}", e)),
     };
 
-    return Ok(PartNumber {
-        value,
-        x_start_loc: parsing_state.parsing_part_number_x_start,
-        x_end_loc: current_x,
-        y_loc: current_y,
-    });
-}
-
-fn is_digit(character: char) -> bool {
-    return character >= '0' && character <= '9';
+    let options: GeneratorOptions = GeneratorOptions {
+        width,
+        height,
+        ..GeneratorOptions::default()
+    };
+    for seed in (seed..).take(count as usize) {
+        match benchmark::run_benchmark(seed, &options) {
+            Ok(result) => print!("{}", benchmark::format_benchmark(&result)),
+            Err(e) => {
+                eprintln!("Error running solvers on generated schematic {}: {}", seed, e);
+                return;
+            }
+        };
+    }
 }
 
-fn is_engine_part(character: char) -> bool {
-    return !is_digit(character) && character != '.';
-}
+// Columns default to one per char, and numbers to unsigned decimal, as in the puzzle.
+// Gear rules default to the puzzle's: '*' with exactly two part numbers, multiplied together.
+// Adjacency defaults to the 8 surrounding cells, without wrapping around the edges.
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut column_model: ColumnModel = ColumnModel::default();
+    let mut number_format: NumberFormat = NumberFormat::default();
+    let mut gear_rules: GearRules = GearRules::default();
+    let mut adjacency: Adjacency = Adjacency::default();
+    let mut character_set: Option<CharacterSet> = None;
+    let mut output_format: Option<OutputFormat> = None;
+    let mut colour: bool = true;
+    let mut diff_format: Option<DiffFormat> = None;
 
-fn is_part_number_for_part(part_number: &PartNumber, part: &EnginePart) -> bool {
-    // A part number is associated to a part if any part of it is adjacent (including diagonally) to the part.
+    let mut i: usize = 0;
+    while i < args.len() {
+        let flag: &String = &args[i];
+        // Flags without a value
+        match flag.as_str() {
+            "--toroidal" => adjacency.toroidal = true,
+            "--render" => set_output_format(&mut output_format, OutputFormat::Render(RenderOptions { colour: true }))?,
+            "--no-colour" => colour = false,
+            "--audit" => set_output_format(&mut output_format, OutputFormat::Audit)?,
+            "--layers" => set_output_format(&mut output_format, OutputFormat::Layers)?,
+            "--stream" => set_output_format(&mut output_format, OutputFormat::Stream)?,
+            "--validate" => {
+                character_set.get_or_insert_with(CharacterSet::default);
+            }
+            _ => {},
+        };
+        if ["--toroidal", "--render", "--no-colour", "--audit", "--layers", "--stream", "--validate"].contains(&flag.as_str()) {
+            i += 1;
+            continue;
+        }
 
-    // Check |part_number.y - part.y| <= 1
-    if (part_number.y_loc as i32 - part.y_loc as i32).abs() > 1 {
-        return false;
+        let value: &String = match args.get(i + 1) {
+            Some(value) => value,
+            None => {
+                return Err(format!("Missing value for {}", flag));
+            }
+        };
+        match flag.as_str() {
+            "--columns" => column_model = value.parse::<ColumnModel>()?,
+            "--numbers" => number_format = value.parse::<NumberFormat>()?,
+            "--gear-symbols" => gear_rules.symbols = value.chars().collect(),
+            "--neighbours" => gear_rules.neighbour_count = value.parse::<NeighbourCount>()?,
+            "--aggregate" => gear_rules.aggregation = value.parse::<Aggregation>()?,
+            "--neighbourhood" => adjacency.neighbourhood = value.parse::<Neighbourhood>()?,
+            // Configuring the character set implies --validate
+            "--blanks" => character_set.get_or_insert_with(CharacterSet::default).blanks = value.chars().collect(),
+            "--symbols" => character_set.get_or_insert_with(CharacterSet::default).symbols = Some(value.chars().collect()),
+            "--export" => {
+                let export_format: ExportFormat = match value.as_str() {
+                    "svg" => ExportFormat::Svg,
+                    "html" => ExportFormat::Html,
+                    _ => {
+                        return Err(format!("Unknown export format {}, expected svg or html", value));
+                    }
+                };
+                set_output_format(&mut output_format, OutputFormat::Export(export_format))?;
+            }
+            "--graph" => {
+                let graph_format: GraphFormat = match value.as_str() {
+                    "dot" => GraphFormat::Dot,
+                    "graphml" => GraphFormat::GraphMl,
+                    "json" => GraphFormat::Json,
+                    _ => {
+                        return Err(format!("Unknown graph format {}, expected dot, graphml or json", value));
+                    }
+                };
+                set_output_format(&mut output_format, OutputFormat::Graph(graph_format))?;
+            }
+            #[cfg(feature = "parallel")]
+            "--parallel" => {
+                let chunk_rows: usize = match value.parse::<usize>() {
+                    Ok(chunk_rows) => chunk_rows,
+                    Err(e) => {
+                        return Err(format!("Error parsing chunk_rows {}: {}", value, e));
+                    }
+                };
+                set_output_format(&mut output_format, OutputFormat::Parallel(chunk_rows))?;
+            }
+            "--diff" => set_output_format(&mut output_format, OutputFormat::Diff(value.clone(), DiffFormat::Text))?,
+            "--diff-format" => {
+                diff_format = match value.as_str() {
+                    "text" => Some(DiffFormat::Text),
+                    "json" => Some(DiffFormat::Json),
+                    _ => {
+                        return Err(format!("Unknown diff format {}, expected text or json", value));
+                    }
+                }
+            }
+            _ => {
+                return Err(format!("Unexpected argument {}", flag));
+            }
+        };
+        i += 2;
     }
 
-    // Check |part_number.x - part.x| <= 1 for at least one x in part_number
-    for x in part_number.x_start_loc..part_number.x_end_loc {
-        if (x as i32 - part.x_loc as i32).abs() <= 1 {
-            return true;
-        }
+    // Options that modify an output format can come before or after it, but not without it
+    if !colour && !matches!(output_format, Some(OutputFormat::Render(_))) {
+        return Err("--no-colour can only be used with --render".to_string());
+    }
+    if diff_format.is_some() && !matches!(output_format, Some(OutputFormat::Diff(_, _))) {
+        return Err("--diff-format can only be used with --diff".to_string());
     }
+    let output_format: OutputFormat = match output_format {
+        Some(OutputFormat::Render(_)) => OutputFormat::Render(RenderOptions { colour }),
+        Some(OutputFormat::Diff(new_input_file, _)) => OutputFormat::Diff(new_input_file, diff_format.unwrap_or(DiffFormat::Text)),
+        Some(output_format) => output_format,
+        None => OutputFormat::Answer,
+    };
 
-    return false;
-}
+    // The streaming window only holds the rows either side of a part
+    if matches!(output_format, OutputFormat::Stream) && adjacency != Adjacency::default() {
+        return Err("--stream only supports the default adjacency".to_string());
+    }
+    // Validation needs the whole schematic, which streaming never loads
+    if matches!(output_format, OutputFormat::Stream) && character_set.is_some() {
+        return Err("--stream can't be combined with --validate, --blanks or --symbols".to_string());
+    }
+    // Layers have their own adjacency, one step along every dimension
+    if matches!(output_format, OutputFormat::Layers) && adjacency != Adjacency::default() {
+        return Err("--layers can't be combined with --neighbourhood or --toroidal".to_string());
+    }
+    #[cfg(feature = "parallel")]
+    if matches!(output_format, OutputFormat::Parallel(_)) && adjacency != Adjacency::default() {
+        return Err("--parallel only supports the default adjacency".to_string());
+    }
 
-struct PartNumberParsingState {
-    part_number_digits: Vec<char>,
-    parsing_part_number_x_start: usize,
+    return Ok(Options {
+        column_model,
+        number_format,
+        gear_rules,
+        adjacency,
+        character_set,
+        output_format,
+    });
 }
 
-// An engine part will be a non-numeric, non-period symbol, and an associated x- and y-coordinate.
-struct EnginePart {
-    value: char,
-    x_loc: usize,
-    y_loc: usize,
+fn set_output_format(output_format: &mut Option<OutputFormat>, new_output_format: OutputFormat) -> Result<(), String> {
+    if output_format.is_some() {
+        return Err("Only one of --render, --export, --graph, --audit, --diff, --layers, --stream and --parallel can be given".to_string());
+    }
+    *output_format = Some(new_output_format);
+    return Ok(());
 }
 
-// A part number can span across multiple x values, but only one y value
-struct PartNumber {
-    value: usize,
-    x_start_loc: usize,
-    x_end_loc: usize,
-    y_loc: usize,
+struct Options {
+    column_model: ColumnModel,
+    number_format: NumberFormat,
+    gear_rules: GearRules,
+    adjacency: Adjacency,
+    // Set if the schematic should be validated before anything else
+    character_set: Option<CharacterSet>,
+    output_format: OutputFormat,
 }
 
-#[cfg(test)]
-mod tests {
-
-    use std::fs;
-
-    #[test]
-    fn test() {
-        // Read input file
-        let input_file: String = "res/test_input.txt".to_string();
-        let contents: String = match fs::read_to_string(input_file) {
-            Ok(f) => f,
-            Err(e) => panic!("{}", e),
-        };
-
-        let result = match crate::sum_part_numbers_from_engine_schematic(&contents) {
-            Ok(res) => res,
-            Err(message) => panic!("{}", message),
-        };
-        assert_eq!(result, 467835);
-    }
+// What to print for the schematic
+enum OutputFormat {
+    Answer,
+    Render(RenderOptions),
+    Export(ExportFormat),
+    Graph(GraphFormat),
+    Audit,
+    // Compare against a newer revision of the schematic
+    Diff(String, DiffFormat),
+    // The answer for a schematic in blank line separated layers
+    Layers,
+    // The answer, reading the schematic a row at a time
+    Stream,
+    // The answer, solving chunks of this many rows in parallel
+    #[cfg(feature = "parallel")]
+    Parallel(usize),
 }
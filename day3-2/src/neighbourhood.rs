@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+// Which cells around a part count as adjacent to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    // The 8 surrounding cells, including diagonals: the puzzle's own definition
+    Moore,
+    // The 4 orthogonally adjacent cells
+    VonNeumann,
+    // Every cell within k rows and k columns
+    Chebyshev(usize),
+    // Every cell reachable in at most k orthogonal steps
+    Manhattan(usize),
+}
+
+impl Neighbourhood {
+    // Whether a cell dx columns and dy rows away (in either direction) is in the neighbourhood
+    pub fn contains(&self, dx: usize, dy: usize) -> bool {
+        return match self {
+            Neighbourhood::Moore => dx <= 1 && dy <= 1,
+            Neighbourhood::VonNeumann => dx + dy <= 1,
+            Neighbourhood::Chebyshev(radius) => dx <= *radius && dy <= *radius,
+            Neighbourhood::Manhattan(radius) => dx + dy <= *radius,
+        };
+    }
+
+    fn radius(&self) -> usize {
+        return match self {
+            Neighbourhood::Moore | Neighbourhood::VonNeumann => 1,
+            Neighbourhood::Chebyshev(radius) | Neighbourhood::Manhattan(radius) => *radius,
+        };
+    }
+}
+
+// Format: "moore", "von-neumann", "chebyshev:K" or "manhattan:K"
+impl FromStr for Neighbourhood {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<Neighbourhood, String> {
+        let (name, radius) = match description.split_once(":") {
+            Some((name, radius)) => match radius.parse::<usize>() {
+                Ok(radius) => (name, Some(radius)),
+                Err(e) => {
+                    return Err(format!("Error parsing neighbourhood radius {}: {}", radius, e));
+                },
+            },
+            None => (description, None),
+        };
+
+        return match (name, radius) {
+            ("moore", None) => Ok(Neighbourhood::Moore),
+            ("von-neumann", None) => Ok(Neighbourhood::VonNeumann),
+            ("chebyshev", Some(radius)) => Ok(Neighbourhood::Chebyshev(radius)),
+            ("manhattan", Some(radius)) => Ok(Neighbourhood::Manhattan(radius)),
+            _ => Err(format!(
+                "Unknown neighbourhood {}, expected moore, von-neumann, chebyshev:K or manhattan:K",
+                description
+            )),
+        };
+    }
+}
+
+// A neighbourhood, and whether it wraps around the edges of the schematic
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adjacency {
+    pub neighbourhood: Neighbourhood,
+    // If set, the left and right edges of the schematic touch, as do the top and bottom
+    pub toroidal: bool,
+}
+
+impl Default for Adjacency {
+    fn default() -> Adjacency {
+        return Adjacency {
+            neighbourhood: Neighbourhood::Moore,
+            toroidal: false,
+        };
+    }
+}
+
+impl Adjacency {
+    // The cells of a width x height schematic adjacent to (x, y), not including (x, y) itself, each listed once
+    pub fn neighbours(&self, x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let radius: isize = self.neighbourhood.radius() as isize;
+        let mut neighbours: Vec<(usize, usize)> = Vec::new();
+
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if !self.neighbourhood.contains(dx.unsigned_abs(), dy.unsigned_abs()) {
+                    continue;
+                }
+                let neighbour: Option<(usize, usize)> = if self.toroidal {
+                    Some((
+                        (x as isize + dx).rem_euclid(width as isize) as usize,
+                        (y as isize + dy).rem_euclid(height as isize) as usize,
+                    ))
+                } else {
+                    match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                        (Some(neighbour_x), Some(neighbour_y)) if neighbour_x < width && neighbour_y < height => {
+                            Some((neighbour_x, neighbour_y))
+                        },
+                        _ => None,
+                    }
+                };
+                if let Some(neighbour) = neighbour {
+                    if neighbour != (x, y) {
+                        neighbours.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        // On a small enough torus, different offsets can wrap around to the same cell
+        neighbours.sort();
+        neighbours.dedup();
+        return neighbours;
+    }
+
+    // Whether (x, y) and (other_x, other_y) are adjacent in a width x height schematic
+    pub fn is_adjacent(&self, (x, y): (usize, usize), (other_x, other_y): (usize, usize), width: usize, height: usize) -> bool {
+        let mut dx: usize = x.abs_diff(other_x);
+        let mut dy: usize = y.abs_diff(other_y);
+        if self.toroidal {
+            dx = dx.min(width - dx);
+            dy = dy.min(height - dy);
+        }
+        return (dx, dy) != (0, 0) && self.neighbourhood.contains(dx, dy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        let moore: Adjacency = Adjacency::default();
+        assert_eq!(moore.neighbours(0, 0, 5, 5), vec![(0, 1), (1, 0), (1, 1)]);
+
+        let von_neumann: Adjacency = Adjacency {
+            neighbourhood: Neighbourhood::VonNeumann,
+            toroidal: true,
+        };
+        assert_eq!(von_neumann.neighbours(0, 0, 5, 5), vec![(0, 1), (0, 4), (1, 0), (4, 0)]);
+
+        let manhattan: Adjacency = Adjacency {
+            neighbourhood: Neighbourhood::Manhattan(2),
+            toroidal: false,
+        };
+        assert_eq!(manhattan.neighbours(2, 2, 5, 5).len(), 12);
+
+        // Every cell of a 3x3 torus is within Chebyshev distance 1 of every other
+        let chebyshev: Adjacency = Adjacency {
+            neighbourhood: Neighbourhood::Chebyshev(3),
+            toroidal: true,
+        };
+        assert_eq!(chebyshev.neighbours(1, 1, 3, 3).len(), 8);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("von-neumann".parse::<Neighbourhood>().unwrap(), Neighbourhood::VonNeumann);
+        assert_eq!("manhattan:3".parse::<Neighbourhood>().unwrap(), Neighbourhood::Manhattan(3));
+        assert!("manhattan".parse::<Neighbourhood>().is_err());
+        assert!("moore:2".parse::<Neighbourhood>().is_err());
+    }
+}
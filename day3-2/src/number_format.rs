@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+// Which spellings the tokenizer recognises as part numbers, beyond runs of decimal digits.
+// Any sign or prefix is part of the number's span, so counts towards its adjacency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NumberFormat {
+    // A '-' or '+' directly before a number. "5-3" is then 5 followed by -3.
+    pub signed: bool,
+    // "0x" or "0X" followed by hexadecimal digits
+    pub hexadecimal: bool,
+    // A single '_' between two digits, which is skipped when reading the value
+    pub underscores: bool,
+}
+
+impl NumberFormat {
+    // Whether the character can be part of a number, so changing it might change how its neighbours are read
+    pub fn is_number_character(&self, character: char) -> bool {
+        return character.is_ascii_digit()
+            || (self.signed && (character == '-' || character == '+'))
+            || (self.hexadecimal && (character == 'x' || character == 'X' || character.is_ascii_hexdigit()))
+            || (self.underscores && character == '_');
+    }
+
+    // The value of the number starting at x in the row, and the x just after it, if a number starts there
+    pub fn read_number(&self, row: &[char], x: usize) -> Result<Option<(i64, usize)>, String> {
+        let mut end: usize = x;
+        // The sign is parsed along with the digits, so the most negative value doesn't overflow
+        let mut digits: String = String::new();
+        if self.signed && (row.get(end) == Some(&'-') || row.get(end) == Some(&'+')) {
+            digits.push(row[end]);
+            end += 1;
+        }
+        let digits_start: usize = digits.len();
+
+        let mut radix: u32 = 10;
+        if self.hexadecimal
+            && row.get(end) == Some(&'0')
+            && (row.get(end + 1) == Some(&'x') || row.get(end + 1) == Some(&'X'))
+            && row.get(end + 2).is_some_and(|character| character.is_ascii_hexdigit())
+        {
+            radix = 16;
+            end += 2;
+        }
+
+        while end < row.len() {
+            if row[end].is_digit(radix) {
+                digits.push(row[end]);
+            } else if !(self.underscores
+                && row[end] == '_'
+                && digits.len() > digits_start
+                && row.get(end + 1).is_some_and(|character| character.is_digit(radix)))
+            {
+                break;
+            }
+            end += 1;
+        }
+        if digits.len() == digits_start {
+            return Ok(None);
+        }
+
+        let value: i64 = match i64::from_str_radix(&digits, radix) {
+            Ok(value) => value,
+            Err(e) => {
+                return Err(format!("Error parsing part number: {}", e));
+            },
+        };
+        return Ok(Some((value, end)));
+    }
+}
+
+// Format: "decimal", or a comma-separated list of "signed", "hex" and "underscores"
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<NumberFormat, String> {
+        let mut number_format: NumberFormat = NumberFormat::default();
+        if description == "decimal" {
+            return Ok(number_format);
+        }
+
+        for option in description.split(",") {
+            match option {
+                "signed" => number_format.signed = true,
+                "hex" => number_format.hexadecimal = true,
+                "underscores" => number_format.underscores = true,
+                _ => {
+                    return Err(format!("Unknown number format {}, expected decimal, signed, hex or underscores", option));
+                },
+            };
+        }
+        return Ok(number_format);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_read_number() {
+        let read = |number_format: &str, row: &str, x: usize| {
+            let row: Vec<char> = row.chars().collect();
+            return number_format.parse::<NumberFormat>().unwrap().read_number(&row, x).unwrap();
+        };
+
+        assert_eq!(read("decimal", "-12.", 0), None);
+        assert_eq!(read("decimal", "-12.", 1), Some((12, 3)));
+        assert_eq!(read("signed", "-12.", 0), Some((-12, 3)));
+        assert_eq!(read("signed", "5-3", 0), Some((5, 1)));
+        assert_eq!(read("signed", "-.", 0), None);
+        assert_eq!(read("hex", "0x1F*", 0), Some((31, 4)));
+        assert_eq!(read("hex", "0xg", 0), Some((0, 1)));
+        assert_eq!(read("signed,hex,underscores", "-0xff_ff_", 0), Some((-65535, 8)));
+        assert_eq!(read("underscores", "1__0", 0), Some((1, 1)));
+        assert_eq!(read("signed", "-9223372036854775808", 0), Some((i64::MIN, 20)));
+        assert_eq!(read("signed,hex", "-0x8000000000000000", 0), Some((i64::MIN, 19)));
+        assert_eq!(read("signed", "+9223372036854775807", 0), Some((i64::MAX, 20)));
+        let row: Vec<char> = "9223372036854775808".chars().collect();
+        assert!(NumberFormat::default().read_number(&row, 0).is_err());
+        assert!("octal".parse::<NumberFormat>().is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use rayon::prelude::*;
+
+use crate::streaming::adjacent_part_number_ids;
+use crate::streaming::finish_gears;
+use crate::streaming::read_row;
+use crate::streaming::WindowRow;
+use crate::ColumnModel;
+use crate::GearRules;
+use crate::NumberFormat;
+use crate::Sums;
+
+// Works out both answers by splitting the schematic into chunks of rows and solving the chunks in parallel.
+// Each chunk also reads the row either side of it, so part numbers and parts next to a chunk boundary still see each
+// other, but only counts the part numbers and gears in its own rows, so nothing is counted twice when the chunks are
+// added up. As with the streaming solver, only the default adjacency is supported.
+pub fn solve_parallel(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    chunk_rows: usize,
+) -> Result<Sums, String> {
+    if chunk_rows == 0 {
+        return Err("Chunks need at least one row".to_string());
+    }
+
+    // As in lay_out, rows are split on '\n' alone
+    let mut lines: Vec<&str> = engine_schematic.split("\n").collect();
+    if lines.last() == Some(&"") {
+        lines.pop();
+    }
+
+    let chunk_starts: Vec<usize> = (0..lines.len()).step_by(chunk_rows).collect();
+    let chunk_sums: Vec<Result<Sums, String>> = chunk_starts
+        .par_iter()
+        .map(|y_start| solve_chunk(&lines, *y_start, (*y_start + chunk_rows).min(lines.len()), column_model, number_format, gear_rules))
+        .collect();
+
+    // Report the first chunk's error, as the whole schematic at once would
+    let mut sums: Sums = Sums {
+        part_number_sum: 0,
+        gear_ratio_sum: 0,
+    };
+    for chunk_sum in chunk_sums {
+        let chunk_sum: Sums = chunk_sum?;
+        sums.part_number_sum = match sums.part_number_sum.checked_add(chunk_sum.part_number_sum) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of part numbers overflowed".to_string());
+            },
+        };
+        sums.gear_ratio_sum = match sums.gear_ratio_sum.checked_add(chunk_sum.gear_ratio_sum) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of gear ratios overflowed".to_string());
+            },
+        };
+    }
+
+    return Ok(sums);
+}
+
+// The sums for the part numbers and gears in rows y_start..y_end
+fn solve_chunk(
+    lines: &[&str],
+    y_start: usize,
+    y_end: usize,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+) -> Result<Sums, String> {
+    // The chunk's own rows, plus the overlapping rows either side, which belong to the neighbouring chunks
+    let overlap_start: usize = y_start.saturating_sub(1);
+    let overlap_end: usize = (y_end + 1).min(lines.len());
+    let mut rows: Vec<WindowRow> = Vec::new();
+    for (y, line) in lines.iter().enumerate().take(overlap_end).skip(overlap_start) {
+        rows.push(read_row(&column_model.lay_out_row(line), y, number_format)?);
+    }
+    let is_own_row = |row_id: usize| (y_start..y_end).contains(&(overlap_start + row_id));
+
+    // Pair up the parts of each row with the part numbers of it and the rows either side, in reading order
+    for part_row_id in 0..rows.len() {
+        for part_number_row_id in part_row_id.saturating_sub(1)..(part_row_id + 2).min(rows.len()) {
+            if !is_own_row(part_row_id) && !is_own_row(part_number_row_id) {
+                continue;
+            }
+
+            let part_number_ids: Vec<Vec<usize>> = rows[part_row_id]
+                .parts
+                .iter()
+                .map(|part| adjacent_part_number_ids(&rows[part_number_row_id].part_number_index, part))
+                .collect();
+            for (part_id, part_number_ids) in part_number_ids.iter().enumerate() {
+                for part_number_id in part_number_ids {
+                    let value: i64 = rows[part_number_row_id].part_numbers[*part_number_id].value;
+                    rows[part_row_id].adjacent_values[part_id].push(value);
+                    rows[part_number_row_id].is_attached[*part_number_id] = true;
+                }
+            }
+        }
+    }
+
+    let mut sums: Sums = Sums {
+        part_number_sum: 0,
+        gear_ratio_sum: 0,
+    };
+    for (row_id, row) in rows.iter().enumerate() {
+        if !is_own_row(row_id) {
+            continue;
+        }
+
+        for (part_number, is_attached) in row.part_numbers.iter().zip(&row.is_attached) {
+            if *is_attached {
+                sums.part_number_sum = match sums.part_number_sum.checked_add(part_number.value) {
+                    Some(sum) => sum,
+                    None => {
+                        return Err("Sum of part numbers overflowed".to_string());
+                    },
+                };
+            }
+        }
+        finish_gears(row, gear_rules, &mut sums, &mut |_| {})?;
+    }
+
+    return Ok(sums);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::generator;
+    use crate::sum_gear_ratios;
+    use crate::sum_part_numbers;
+    use crate::Adjacency;
+    use crate::Aggregation;
+    use crate::NeighbourCount;
+    use generator::GeneratorOptions;
+    use std::fs;
+
+    #[test]
+    fn test_solve_parallel() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        for chunk_rows in 1..12 {
+            let sums: Sums = solve_parallel(&contents, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default(), chunk_rows).unwrap();
+            assert_eq!(sums, Sums { part_number_sum: 4361, gear_ratio_sum: 467835 });
+        }
+        assert!(solve_parallel(&contents, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default(), 0).is_err());
+    }
+
+    #[test]
+    fn test_matches_whole_schematic() {
+        let gear_rules: GearRules = GearRules {
+            symbols: vec!['*', '#'],
+            neighbour_count: NeighbourCount::AtLeast(1),
+            aggregation: Aggregation::Custom(|values| values.iter().enumerate().map(|(i, value)| (i as i64 + 1) * value).sum()),
+        };
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 20,
+            height: 30,
+            number_density: 0.3,
+            symbol_density: 0.3,
+            symbols: "#-".chars().collect(),
+            gear_frequency: 0.5,
+        };
+
+        for seed in 0..10 {
+            let mut engine_schematic: String = generator::generate_schematic(seed, &options).unwrap().engine_schematic;
+            // Ragged rows and no trailing newline
+            if seed % 2 == 0 {
+                engine_schematic = engine_schematic.lines().map(|line| &line[..(line.len() + seed as usize) % 21]).collect::<Vec<&str>>().join("\n");
+            }
+
+            let expected: Sums = Sums {
+                part_number_sum: sum_part_numbers(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &Adjacency::default()).unwrap(),
+                gear_ratio_sum: sum_gear_ratios(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &gear_rules, &Adjacency::default()).unwrap(),
+            };
+            for chunk_rows in [1, 2, 3, 7, 30, 100] {
+                assert_eq!(solve_parallel(&engine_schematic, &ColumnModel::Char, &NumberFormat::default(), &gear_rules, chunk_rows).unwrap(), expected);
+            }
+        }
+    }
+}
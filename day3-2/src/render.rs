@@ -0,0 +1,190 @@
+use std::io;
+use std::io::Write;
+
+use crate::analyse_engine_schematic;
+use crate::Adjacency;
+use crate::ColumnModel;
+use crate::AnalysedSchematic;
+use crate::GearRules;
+use crate::NumberFormat;
+
+const RESET: &str = "\x1b[0m";
+
+// How each cell of the schematic is drawn
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CellStyle {
+    Blank,
+    AttachedPartNumber,
+    UnattachedPartNumber,
+    Gear,
+    // A part with no adjacent part numbers at all
+    OrphanPart,
+    Part,
+}
+
+impl CellStyle {
+    fn escape_code(&self) -> &'static str {
+        return match self {
+            CellStyle::Blank => "\x1b[2m",
+            CellStyle::AttachedPartNumber => "\x1b[32m",
+            CellStyle::UnattachedPartNumber => "\x1b[31m",
+            CellStyle::Gear => "\x1b[1;33m",
+            CellStyle::OrphanPart => "\x1b[35;7m",
+            CellStyle::Part => "\x1b[36m",
+        };
+    }
+}
+
+pub struct RenderOptions {
+    // Without colour the schematic is printed as is, and only the gear and orphan annotations mark anything out
+    pub colour: bool,
+}
+
+// Writes the schematic one row at a time, each followed by notes on the gears and orphan parts in it.
+// Colours are reset at the end of every row, so the output can be piped through a pager (e.g. `less -R`)
+// and any screenful still renders correctly.
+pub fn render_schematic(
+    output: &mut impl Write,
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    adjacency: &Adjacency,
+    options: &RenderOptions,
+) -> Result<(), String> {
+    let analysed_schematic: AnalysedSchematic = analyse_engine_schematic(engine_schematic, column_model, number_format, adjacency)?;
+    let styles: Vec<Vec<CellStyle>> = cell_styles(&analysed_schematic, gear_rules)?;
+
+    let result: io::Result<()> = write_schematic(output, &analysed_schematic, &styles, gear_rules, options);
+    return match result {
+        // The reader (e.g. a pager) stopped reading, which is how they say they've seen enough
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        Err(e) => Err(format!("Error writing schematic: {}", e)),
+        Ok(()) => Ok(()),
+    };
+}
+
+fn cell_styles(analysed_schematic: &AnalysedSchematic, gear_rules: &GearRules) -> Result<Vec<Vec<CellStyle>>, String> {
+    let schematic = &analysed_schematic.schematic;
+    let mut styles: Vec<Vec<CellStyle>> = vec![vec![CellStyle::Blank; schematic.width()]; schematic.height()];
+
+    for (part_number_id, part_number) in analysed_schematic.part_numbers.iter().enumerate() {
+        let style: CellStyle = if analysed_schematic.is_attached[part_number_id] {
+            CellStyle::AttachedPartNumber
+        } else {
+            CellStyle::UnattachedPartNumber
+        };
+        styles[part_number.y_loc][part_number.x_start_loc..part_number.x_end_loc].fill(style);
+    }
+
+    for (part_id, part) in analysed_schematic.parts.iter().enumerate() {
+        let style: CellStyle = if analysed_schematic.gear_ratio(part_id, gear_rules)?.is_some() {
+            CellStyle::Gear
+        } else if analysed_schematic.adjacent_part_number_ids[part_id].is_empty() {
+            CellStyle::OrphanPart
+        } else {
+            CellStyle::Part
+        };
+        styles[part.y_loc][part.x_loc..part.x_loc + part.width].fill(style);
+    }
+
+    return Ok(styles);
+}
+
+fn write_schematic(
+    output: &mut impl Write,
+    analysed_schematic: &AnalysedSchematic,
+    styles: &Vec<Vec<CellStyle>>,
+    gear_rules: &GearRules,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    if options.colour {
+        writeln!(
+            output,
+            "{}attached{} {}unattached{} {}gear{} {}orphan{} {}part{}",
+            CellStyle::AttachedPartNumber.escape_code(), RESET,
+            CellStyle::UnattachedPartNumber.escape_code(), RESET,
+            CellStyle::Gear.escape_code(), RESET,
+            CellStyle::OrphanPart.escape_code(), RESET,
+            CellStyle::Part.escape_code(), RESET,
+        )?;
+    }
+
+    // Parts are in reading order, so each row's notes come from the next run of parts
+    let mut part_id: usize = 0;
+
+    for (y, row) in analysed_schematic.glyphs.rows().enumerate() {
+        let mut line: String = String::new();
+        let mut current_style: Option<CellStyle> = None;
+        for (x, glyph) in row.iter().enumerate() {
+            if options.colour && current_style != Some(styles[y][x]) {
+                line += styles[y][x].escape_code();
+                current_style = Some(styles[y][x]);
+            }
+            line += glyph;
+        }
+        if options.colour {
+            line += RESET;
+        }
+
+        let mut notes: Vec<String> = Vec::new();
+        while part_id < analysed_schematic.parts.len() && analysed_schematic.parts[part_id].y_loc == y {
+            let part = &analysed_schematic.parts[part_id];
+            // Ratios have already been checked while styling the cells
+            if let Ok(Some(gear_ratio)) = analysed_schematic.gear_ratio(part_id, gear_rules) {
+                notes.push(format!("{} at {} = {}", part.value, part.x_loc, gear_ratio));
+            } else if analysed_schematic.adjacent_part_number_ids[part_id].is_empty() {
+                notes.push(format!("{} at {} orphan", part.value, part.x_loc));
+            }
+            part_id += 1;
+        }
+        if !notes.is_empty() {
+            line += &format!("  {}", notes.join(", "));
+        }
+
+        writeln!(output, "{}", line)?;
+    }
+
+    return output.flush();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn render(engine_schematic: &str, column_model: ColumnModel, colour: bool) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        let options: RenderOptions = RenderOptions { colour };
+        render_schematic(
+            &mut output,
+            &engine_schematic.to_string(),
+            &column_model,
+            &NumberFormat::default(),
+            &GearRules::default(),
+            &Adjacency::default(),
+            &options,
+        )
+        .unwrap();
+        return String::from_utf8(output).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let engine_schematic: &str = "467..114..\n...*......\n..35...#..\n";
+
+        assert_eq!(
+            render(engine_schematic, ColumnModel::Char, false),
+            "467..114..\n...*......  * at 3 = 16345\n..35...#..  # at 7 orphan\n"
+        );
+
+        let rendered: String = render(engine_schematic, ColumnModel::Char, true);
+        assert!(rendered.contains("\x1b[32m467\x1b[2m..\x1b[31m114"));
+        assert!(rendered.contains("\x1b[1;33m*"));
+        assert!(rendered.contains("\x1b[35;7m#"));
+
+        // The wide character is drawn once, and highlighted as a part along with the column it covers
+        let rendered: String = render("\u{4e2d}..\n..5\n", ColumnModel::DisplayWidth, true);
+        assert!(rendered.contains("\x1b[36m\u{4e2d}\x1b[2m..\x1b[0m\n"));
+    }
+}
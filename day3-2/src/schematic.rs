@@ -0,0 +1,537 @@
+use std::fmt;
+
+use grid::Grid;
+
+use crate::graph::PartNode;
+use crate::graph::PartNumberNode;
+use crate::is_engine_part;
+use crate::parse_engine_schematic;
+use crate::tokenize_row;
+use crate::Adjacency;
+use crate::GearRules;
+use crate::NumberFormat;
+use crate::PartNumber;
+
+// A schematic that can be edited one cell at a time, keeping the part number and gear ratio sums up to date as it goes.
+// Only the part numbers and gears around an edited cell are looked at again. Cells are always single chars.
+// It can also be asked what's in a cell, and what touches what, without working out the sums again.
+pub struct Schematic {
+    schematic: Grid<char>,
+    number_format: NumberFormat,
+    gear_rules: GearRules,
+    adjacency: Adjacency,
+    // Part numbers by id, with the ids of removed part numbers reused for new ones
+    part_numbers: Vec<Option<PartNumber>>,
+    free_part_number_ids: Vec<usize>,
+    part_number_index: Grid<Option<usize>>,
+    // For each part number id, whether it's adjacent to any part
+    is_attached: Vec<bool>,
+    // The ratio of the gear in each cell, if there is one
+    gear_ratios: Grid<Option<i64>>,
+    part_number_sum: i64,
+    gear_ratio_sum: i64,
+}
+
+// What a cell of the schematic holds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Blank,
+    // Any cell of a part number, including its sign or prefix
+    PartNumber(PartNumberNode),
+    Part(PartNode),
+    // A part that's a gear under the schematic's gear rules, with its ratio
+    Gear(PartNode, i64),
+}
+
+impl Schematic {
+    pub fn parse(
+        engine_schematic: &String,
+        number_format: NumberFormat,
+        gear_rules: GearRules,
+        adjacency: Adjacency,
+    ) -> Result<Schematic, String> {
+        // Short rows are padded with blank cells, as for the one-off sums
+        let schematic: Grid<char> = Grid::parse_padded(engine_schematic, '.');
+        let (_, part_numbers) = parse_engine_schematic(&schematic, &number_format)?;
+        let (width, height) = (schematic.width(), schematic.height());
+
+        let mut result: Schematic = Schematic {
+            schematic,
+            number_format,
+            gear_rules,
+            adjacency,
+            part_numbers: Vec::new(),
+            free_part_number_ids: Vec::new(),
+            part_number_index: Grid::new(width, height, None),
+            is_attached: Vec::new(),
+            gear_ratios: Grid::new(width, height, None),
+            part_number_sum: 0,
+            gear_ratio_sum: 0,
+        };
+
+        // Every part number has to be in place before attachments are checked, to tell signs that start them from parts
+        for part_number in part_numbers {
+            result.insert_part_number(part_number);
+        }
+        for part_number_id in 0..result.part_numbers.len() {
+            result.update_attachment(part_number_id)?;
+        }
+        for y in 0..height {
+            for x in 0..width {
+                result.update_gear_ratio(x, y)?;
+            }
+        }
+
+        return Ok(result);
+    }
+
+    pub fn width(&self) -> usize {
+        return self.schematic.width();
+    }
+
+    pub fn height(&self) -> usize {
+        return self.schematic.height();
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        return self.schematic.get(x, y).copied();
+    }
+
+    // The part 1 answer for the schematic as it currently stands
+    pub fn part_number_sum(&self) -> i64 {
+        return self.part_number_sum;
+    }
+
+    // The part 2 answer for the schematic as it currently stands
+    pub fn gear_ratio_sum(&self) -> i64 {
+        return self.gear_ratio_sum;
+    }
+
+    // None if (x, y) is outside the schematic
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        if !self.schematic.in_bounds(x, y) {
+            return None;
+        }
+
+        if let Some(part_number_id) = self.part_number_index[(x, y)] {
+            return Some(Cell::PartNumber(self.part_number_node(part_number_id)));
+        }
+        if !self.is_part((x, y)) {
+            return Some(Cell::Blank);
+        }
+        return match self.gear_ratios[(x, y)] {
+            Some(gear_ratio) => Some(Cell::Gear(self.part_node((x, y)), gear_ratio)),
+            None => Some(Cell::Part(self.part_node((x, y)))),
+        };
+    }
+
+    // The part numbers touching the part at (x, y) in reading order, or none if there's no part there
+    pub fn part_numbers_adjacent_to(&self, x: usize, y: usize) -> Vec<PartNumberNode> {
+        if !self.schematic.in_bounds(x, y) || !self.is_part((x, y)) {
+            return Vec::new();
+        }
+
+        let mut part_number_ids: Vec<usize> = Vec::new();
+        for neighbour in self.adjacency.neighbours(x, y, self.width(), self.height()) {
+            if let Some(part_number_id) = self.part_number_index[neighbour] {
+                if !part_number_ids.contains(&part_number_id) {
+                    part_number_ids.push(part_number_id);
+                }
+            }
+        }
+
+        let mut part_numbers: Vec<PartNumberNode> = part_number_ids.iter().map(|id| self.part_number_node(*id)).collect();
+        part_numbers.sort_by_key(|part_number| (part_number.y_loc, part_number.x_start_loc));
+        return part_numbers;
+    }
+
+    // The parts touching the part number covering (x, y) in reading order, or none if there's no part number there
+    pub fn parts_adjacent_to(&self, x: usize, y: usize) -> Vec<PartNode> {
+        let part_number_id: usize = match self.schematic.in_bounds(x, y).then(|| self.part_number_index[(x, y)]).flatten() {
+            Some(part_number_id) => part_number_id,
+            None => {
+                return Vec::new();
+            },
+        };
+        let part_number: &PartNumber = self.part_numbers[part_number_id].as_ref().unwrap();
+
+        let mut cells: Vec<(usize, usize)> = Vec::new();
+        for part_number_x in part_number.x_start_loc..part_number.x_end_loc {
+            for neighbour in self.adjacency.neighbours(part_number_x, part_number.y_loc, self.width(), self.height()) {
+                if self.is_part(neighbour) && !cells.contains(&neighbour) {
+                    cells.push(neighbour);
+                }
+            }
+        }
+
+        cells.sort_by_key(|(x, y)| (*y, *x));
+        return cells.into_iter().map(|cell| self.part_node(cell)).collect();
+    }
+
+    // Every part number with the given value, in reading order
+    pub fn part_numbers_with_value(&self, value: i64) -> Vec<PartNumberNode> {
+        let mut part_numbers: Vec<PartNumberNode> = self
+            .part_numbers
+            .iter()
+            .flatten()
+            .filter(|part_number| part_number.value == value)
+            .map(PartNumberNode::from)
+            .collect();
+        part_numbers.sort_by_key(|part_number| (part_number.y_loc, part_number.x_start_loc));
+        return part_numbers;
+    }
+
+    // Every gear with a ratio greater than the given one, in reading order
+    pub fn gears_with_ratio_above(&self, ratio: i64) -> Vec<(PartNode, i64)> {
+        let mut gears: Vec<(PartNode, i64)> = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if let Some(gear_ratio) = self.gear_ratios[(x, y)] {
+                    if gear_ratio > ratio {
+                        gears.push((self.part_node((x, y)), gear_ratio));
+                    }
+                }
+            }
+        }
+        return gears;
+    }
+
+    // Changes the cell at (x, y), updating the part numbers and gears affected by it.
+    // If this fails because a value overflowed, the sums are no longer reliable and the schematic should be parsed again.
+    pub fn set(&mut self, x: usize, y: usize, character: char) -> Result<(), String> {
+        let (width, height) = (self.width(), self.height());
+        if !self.schematic.in_bounds(x, y) {
+            return Err(format!("({}, {}) is outside the {}x{} schematic", x, y, width, height));
+        }
+        if self.schematic[(x, y)] == character {
+            return Ok(());
+        }
+
+        // Changing the cell can change how the whole run of number characters around it is read, splitting, joining,
+        // extending or shortening part numbers, and turning signs and prefixes into parts or back
+        let row: &[char] = self.schematic.row(y).unwrap();
+        let mut x_start: usize = x;
+        while x_start > 0 && self.number_format.is_number_character(row[x_start - 1]) {
+            x_start -= 1;
+        }
+        let mut x_end: usize = x + 1;
+        while x_end < width && self.number_format.is_number_character(row[x_end]) {
+            x_end += 1;
+        }
+
+        let mut retokenised_part_number_ids: Vec<usize> = Vec::new();
+        for run_x in x_start..x_end {
+            if let Some(part_number_id) = self.part_number_index[(run_x, y)] {
+                if !retokenised_part_number_ids.contains(&part_number_id) {
+                    retokenised_part_number_ids.push(part_number_id);
+                }
+            }
+        }
+        for part_number_id in retokenised_part_number_ids {
+            self.remove_part_number(part_number_id);
+        }
+
+        self.schematic[(x, y)] = character;
+
+        // Either side of the run is a character that can't be in a number, so no other part numbers can change
+        let mut attachment_part_number_ids: Vec<usize> = Vec::new();
+        for part_number in tokenize_row(&self.schematic.row(y).unwrap()[x_start..x_end], y, x_start, &self.number_format)? {
+            attachment_part_number_ids.push(self.insert_part_number(part_number));
+        }
+        let changed_cells: Vec<(usize, usize)> = (x_start..x_end).map(|run_x| (run_x, y)).collect();
+
+        // Part numbers around the run may have gained or lost parts in it
+        for (changed_x, changed_y) in &changed_cells {
+            for neighbour in self.adjacency.neighbours(*changed_x, *changed_y, width, height) {
+                if let Some(part_number_id) = self.part_number_index[neighbour] {
+                    if !attachment_part_number_ids.contains(&part_number_id) {
+                        attachment_part_number_ids.push(part_number_id);
+                    }
+                }
+            }
+        }
+        for part_number_id in attachment_part_number_ids {
+            self.update_attachment(part_number_id)?;
+        }
+
+        // Gears around the run may have gained or lost part numbers, and cells in it may have become or stopped being gears
+        let mut gear_cells: Vec<(usize, usize)> = changed_cells.clone();
+        for (changed_x, changed_y) in changed_cells {
+            gear_cells.extend(self.adjacency.neighbours(changed_x, changed_y, width, height));
+        }
+        gear_cells.sort();
+        gear_cells.dedup();
+        for (gear_x, gear_y) in gear_cells {
+            self.update_gear_ratio(gear_x, gear_y)?;
+        }
+
+        return Ok(());
+    }
+
+    fn insert_part_number(&mut self, part_number: PartNumber) -> usize {
+        let part_number_id: usize = match self.free_part_number_ids.pop() {
+            Some(part_number_id) => part_number_id,
+            None => {
+                self.part_numbers.push(None);
+                self.is_attached.push(false);
+                self.part_numbers.len() - 1
+            },
+        };
+
+        for x in part_number.x_start_loc..part_number.x_end_loc {
+            self.part_number_index[(x, part_number.y_loc)] = Some(part_number_id);
+        }
+        self.part_numbers[part_number_id] = Some(part_number);
+        self.is_attached[part_number_id] = false;
+
+        return part_number_id;
+    }
+
+    fn remove_part_number(&mut self, part_number_id: usize) {
+        let part_number: PartNumber = self.part_numbers[part_number_id].take().unwrap();
+        for x in part_number.x_start_loc..part_number.x_end_loc {
+            self.part_number_index[(x, part_number.y_loc)] = None;
+        }
+        if self.is_attached[part_number_id] {
+            self.part_number_sum -= part_number.value;
+            self.is_attached[part_number_id] = false;
+        }
+        self.free_part_number_ids.push(part_number_id);
+    }
+
+    fn part_number_node(&self, part_number_id: usize) -> PartNumberNode {
+        return PartNumberNode::from(self.part_numbers[part_number_id].as_ref().unwrap());
+    }
+
+    fn part_node(&self, (x, y): (usize, usize)) -> PartNode {
+        return PartNode {
+            value: self.schematic[(x, y)],
+            x_loc: x,
+            y_loc: y,
+        };
+    }
+
+    // Signs and prefixes that start a part number aren't parts
+    fn is_part(&self, cell: (usize, usize)) -> bool {
+        return is_engine_part(self.schematic[cell]) && self.part_number_index[cell].is_none();
+    }
+
+    // Re-checks whether the part number is adjacent to any part, updating the part number sum
+    fn update_attachment(&mut self, part_number_id: usize) -> Result<(), String> {
+        let (width, height) = (self.width(), self.height());
+        let part_number: &PartNumber = self.part_numbers[part_number_id].as_ref().unwrap();
+
+        let is_attached: bool = (part_number.x_start_loc..part_number.x_end_loc).any(|x| {
+            self.adjacency
+                .neighbours(x, part_number.y_loc, width, height)
+                .iter()
+                .any(|neighbour| self.is_part(*neighbour))
+        });
+
+        if is_attached && !self.is_attached[part_number_id] {
+            self.part_number_sum = match self.part_number_sum.checked_add(part_number.value) {
+                Some(sum) => sum,
+                None => {
+                    return Err("Sum of part numbers overflowed".to_string());
+                },
+            };
+        } else if !is_attached && self.is_attached[part_number_id] {
+            self.part_number_sum -= part_number.value;
+        }
+        self.is_attached[part_number_id] = is_attached;
+
+        return Ok(());
+    }
+
+    // Re-calculates the ratio of the gear at (x, y), if there is one, updating the gear ratio sum
+    fn update_gear_ratio(&mut self, x: usize, y: usize) -> Result<(), String> {
+        if let Some(gear_ratio) = self.gear_ratios[(x, y)].take() {
+            self.gear_ratio_sum -= gear_ratio;
+        }
+
+        let character: char = self.schematic[(x, y)];
+        if !self.is_part((x, y)) || !self.gear_rules.symbols.contains(&character) {
+            return Ok(());
+        }
+
+        let mut part_number_ids: Vec<usize> = Vec::new();
+        for neighbour in self.adjacency.neighbours(x, y, self.width(), self.height()) {
+            if let Some(part_number_id) = self.part_number_index[neighbour] {
+                if !part_number_ids.contains(&part_number_id) {
+                    part_number_ids.push(part_number_id);
+                }
+            }
+        }
+        if !self.gear_rules.neighbour_count.allows(part_number_ids.len()) {
+            return Ok(());
+        }
+
+        // Aggregate in reading order, as the one-off sums do
+        let mut part_numbers: Vec<&PartNumber> = part_number_ids.iter().map(|id| self.part_numbers[*id].as_ref().unwrap()).collect();
+        part_numbers.sort_by_key(|part_number| (part_number.y_loc, part_number.x_start_loc));
+        let values: Vec<i64> = part_numbers.iter().map(|part_number| part_number.value).collect();
+
+        let gear_ratio: i64 = match self.gear_rules.aggregation.apply(&values) {
+            Some(gear_ratio) => gear_ratio,
+            None => {
+                return Err(format!("Gear ratio overflowed for gear at ({}, {})", x, y));
+            },
+        };
+        self.gear_ratio_sum = match self.gear_ratio_sum.checked_add(gear_ratio) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of gear ratios overflowed".to_string());
+            },
+        };
+        self.gear_ratios[(x, y)] = Some(gear_ratio);
+
+        return Ok(());
+    }
+}
+
+impl fmt::Display for Schematic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.schematic);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::generator;
+    use crate::Aggregation;
+    use crate::ColumnModel;
+    use crate::NeighbourCount;
+    use crate::Neighbourhood;
+    use generator::GeneratorOptions;
+    use std::fs;
+
+    #[test]
+    fn test_set() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        let mut schematic: Schematic = Schematic::parse(&contents, NumberFormat::default(), GearRules::default(), Adjacency::default()).unwrap();
+        assert_eq!((schematic.part_number_sum(), schematic.gear_ratio_sum()), (4361, 467835));
+
+        // Attaching 114 to the gear at (3, 1) stops it being a gear
+        schematic.set(4, 0, '.').unwrap();
+        schematic.set(4, 0, '9').unwrap();
+        assert_eq!(schematic.part_number_sum(), 4361 + 9114);
+        assert_eq!(schematic.gear_ratio_sum(), 467835 - 467 * 35);
+        assert!(schematic.set(10, 0, '*').is_err());
+    }
+
+    #[test]
+    fn test_queries() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        let schematic: Schematic = Schematic::parse(&contents, NumberFormat::default(), GearRules::default(), Adjacency::default()).unwrap();
+        let gear: PartNode = PartNode { value: '*', x_loc: 3, y_loc: 1 };
+        let part_number_467: PartNumberNode = PartNumberNode { value: 467, x_start_loc: 0, x_end_loc: 3, y_loc: 0 };
+        let part_number_35: PartNumberNode = PartNumberNode { value: 35, x_start_loc: 2, x_end_loc: 4, y_loc: 2 };
+
+        assert_eq!(schematic.cell(3, 1), Some(Cell::Gear(gear.clone(), 16345)));
+        assert_eq!(schematic.cell(1, 0), Some(Cell::PartNumber(part_number_467.clone())));
+        assert_eq!(schematic.cell(6, 3), Some(Cell::Part(PartNode { value: '#', x_loc: 6, y_loc: 3 })));
+        assert_eq!(schematic.cell(0, 1), Some(Cell::Blank));
+        assert_eq!(schematic.cell(10, 0), None);
+
+        assert_eq!(schematic.part_numbers_adjacent_to(3, 1), vec![part_number_467, part_number_35]);
+        assert!(schematic.part_numbers_adjacent_to(0, 0).is_empty());
+        assert_eq!(schematic.parts_adjacent_to(3, 2), vec![gear]);
+        assert!(schematic.parts_adjacent_to(5, 0).is_empty());
+        assert_eq!(schematic.part_numbers_with_value(114)[0].x_start_loc, 5);
+
+        let gears: Vec<(PartNode, i64)> = schematic.gears_with_ratio_above(16345);
+        assert_eq!(gears.len(), 1);
+        assert_eq!(gears[0].1, 451490);
+    }
+
+    #[test]
+    fn test_queries_match_comparison() {
+        let adjacency: Adjacency = Adjacency {
+            neighbourhood: Neighbourhood::Chebyshev(2),
+            toroidal: true,
+        };
+        let options: GeneratorOptions = GeneratorOptions {
+            width: 12,
+            height: 12,
+            number_density: 0.3,
+            symbol_density: 0.2,
+            symbols: vec!['#'],
+            gear_frequency: 0.5,
+        };
+        for seed in 0..20 {
+            let engine_schematic: String = generator::generate_schematic(seed, &options).unwrap().engine_schematic;
+            let schematic: Schematic = Schematic::parse(&engine_schematic, NumberFormat::default(), GearRules::default(), adjacency).unwrap();
+            let grid: Grid<char> = Grid::parse(&engine_schematic).unwrap();
+            let (parts, part_numbers) = parse_engine_schematic(&grid, &NumberFormat::default()).unwrap();
+            for part in &parts {
+                let expected: Vec<PartNumberNode> = part_numbers
+                    .iter()
+                    .filter(|part_number| crate::is_part_number_for_part(part_number, part, grid.width(), grid.height(), &adjacency))
+                    .map(PartNumberNode::from)
+                    .collect();
+                assert_eq!(schematic.part_numbers_adjacent_to(part.x_loc, part.y_loc), expected);
+            }
+            for part_number in &part_numbers {
+                let expected: Vec<PartNode> = parts
+                    .iter()
+                    .filter(|part| crate::is_part_number_for_part(part_number, part, grid.width(), grid.height(), &adjacency))
+                    .map(PartNode::from)
+                    .collect();
+                assert_eq!(schematic.parts_adjacent_to(part_number.x_end_loc - 1, part_number.y_loc), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_matches_parse() {
+        let configurations: Vec<(NumberFormat, GearRules, Adjacency)> = vec![
+            (NumberFormat::default(), GearRules::default(), Adjacency::default()),
+            (
+                NumberFormat {
+                    signed: true,
+                    hexadecimal: true,
+                    underscores: true,
+                },
+                GearRules {
+                    symbols: vec!['*', '-'],
+                    neighbour_count: NeighbourCount::AtLeast(1),
+                    aggregation: Aggregation::Sum,
+                },
+                Adjacency {
+                    neighbourhood: Neighbourhood::Chebyshev(2),
+                    toroidal: true,
+                },
+            ),
+        ];
+
+        for (number_format, gear_rules, adjacency) in configurations {
+            let mut seed: u64 = 5;
+            let mut schematic: Schematic = Schematic::parse(&"..........\n".repeat(10), number_format, gear_rules.clone(), adjacency).unwrap();
+            for _ in 0..2000 {
+                let roll: u64 = generator::next_random(&mut seed);
+                let character: char = match roll % 12 {
+                    0..=2 => '.',
+                    3 => '*',
+                    4 => '-',
+                    5 => ['x', 'a', '_'][((roll >> 8) % 3) as usize],
+                    _ => char::from(b'0' + ((roll >> 8) % 10) as u8),
+                };
+                let x: usize = (generator::next_random(&mut seed) % 10) as usize;
+                let y: usize = (generator::next_random(&mut seed) % 10) as usize;
+                schematic.set(x, y, character).unwrap();
+
+                let engine_schematic: String = schematic.to_string();
+                assert_eq!(
+                    schematic.part_number_sum(),
+                    crate::sum_part_numbers(&engine_schematic, &ColumnModel::default(), &number_format, &adjacency).unwrap()
+                );
+                assert_eq!(
+                    schematic.gear_ratio_sum(),
+                    crate::sum_gear_ratios(&engine_schematic, &ColumnModel::default(), &number_format, &gear_rules, &adjacency).unwrap()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use std::io::BufRead;
+
+use crate::columns::Column;
+use crate::graph::PartNode;
+use crate::graph::PartNumberNode;
+use crate::is_engine_part;
+use crate::tokenize_row;
+use crate::ColumnModel;
+use crate::EnginePart;
+use crate::GearRules;
+use crate::NumberFormat;
+use crate::PartNumber;
+use crate::Sums;
+
+// Something the streaming solver has found out for certain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    // A part number with at least one adjacent part, sent as soon as the first part is seen
+    PartNumber(PartNumberNode),
+    // A gear and its ratio, sent once the row after it has been read
+    Gear(PartNode, i64),
+}
+
+// A row still in the window, along with everything known about it so far
+pub(crate) struct WindowRow {
+    pub(crate) part_numbers: Vec<PartNumber>,
+    pub(crate) is_attached: Vec<bool>,
+    // The index of the part number covering each column, if any
+    pub(crate) part_number_index: Vec<Option<usize>>,
+    pub(crate) parts: Vec<EnginePart>,
+    // The values of the part numbers found next to each part so far, in reading order
+    pub(crate) adjacent_values: Vec<Vec<i64>>,
+}
+
+// Works out both answers while reading the schematic one row at a time, so only the row being read and the one before
+// it are ever held in memory. The results match sum_part_numbers and sum_gear_ratios with the default adjacency, which
+// is the only one supported: wider neighbourhoods and wrapping around the edges need more than the rows either side.
+pub fn solve_streaming(
+    input: &mut impl BufRead,
+    column_model: &ColumnModel,
+    number_format: &NumberFormat,
+    gear_rules: &GearRules,
+    on_event: &mut impl FnMut(StreamEvent),
+) -> Result<Sums, String> {
+    let mut sums: Sums = Sums {
+        part_number_sum: 0,
+        gear_ratio_sum: 0,
+    };
+    let mut previous_row: Option<WindowRow> = None;
+    let mut line: String = String::new();
+    let mut y: usize = 0;
+
+    loop {
+        line.clear();
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {},
+            Err(e) => {
+                return Err(format!("Error reading row {}: {}", y, e));
+            },
+        };
+        // As in lay_out, rows are split on '\n' alone
+        let columns: Vec<Column> = column_model.lay_out_row(line.strip_suffix("\n").unwrap_or(&line));
+        let mut row: WindowRow = read_row(&columns, y, number_format)?;
+
+        // Every part and part number pair is checked once, when the later of their rows is read
+        match &mut previous_row {
+            Some(previous_row) => {
+                attach(&mut row, previous_row, &mut sums, on_event)?;
+                attach_within(&mut row, &mut sums, on_event)?;
+                attach(previous_row, &mut row, &mut sums, on_event)?;
+                // Nothing more can touch the previous row's parts
+                finish_gears(previous_row, gear_rules, &mut sums, on_event)?;
+            },
+            None => attach_within(&mut row, &mut sums, on_event)?,
+        };
+
+        previous_row = Some(row);
+        y += 1;
+    }
+
+    if let Some(previous_row) = &previous_row {
+        finish_gears(previous_row, gear_rules, &mut sums, on_event)?;
+    }
+
+    return Ok(sums);
+}
+
+pub(crate) fn read_row(columns: &[Column], y: usize, number_format: &NumberFormat) -> Result<WindowRow, String> {
+    let cells: Vec<char> = columns.iter().map(|column| column.character).collect();
+    let part_numbers: Vec<PartNumber> = tokenize_row(&cells, y, 0, number_format)?;
+
+    let mut part_number_index: Vec<Option<usize>> = vec![None; cells.len()];
+    for (part_number_id, part_number) in part_numbers.iter().enumerate() {
+        part_number_index[part_number.x_start_loc..part_number.x_end_loc].fill(Some(part_number_id));
+    }
+
+    let mut parts: Vec<EnginePart> = Vec::new();
+    for (x, character) in cells.iter().copied().enumerate() {
+        if part_number_index[x].is_none() && is_engine_part(character) {
+            // A wide glyph covers the columns after it, as in analyse_engine_schematic
+            let mut width: usize = 1;
+            while x + width < columns.len() && columns[x + width].glyph.is_empty() {
+                width += 1;
+            }
+            parts.push(EnginePart {
+                value: character,
+                x_loc: x,
+                y_loc: y,
+                layer_locs: Vec::new(),
+                width,
+            });
+        }
+    }
+
+    return Ok(WindowRow {
+        is_attached: vec![false; part_numbers.len()],
+        adjacent_values: vec![Vec::new(); parts.len()],
+        part_numbers,
+        part_number_index,
+        parts,
+    });
+}
+
+// Pairs up the parts of one row with the part numbers of a neighbouring row
+fn attach(
+    part_row: &mut WindowRow,
+    part_number_row: &mut WindowRow,
+    sums: &mut Sums,
+    on_event: &mut impl FnMut(StreamEvent),
+) -> Result<(), String> {
+    for (part, adjacent_values) in part_row.parts.iter().zip(&mut part_row.adjacent_values) {
+        for part_number_id in adjacent_part_number_ids(&part_number_row.part_number_index, part) {
+            adjacent_values.push(part_number_row.part_numbers[part_number_id].value);
+            mark_attached(part_number_row, part_number_id, sums, on_event)?;
+        }
+    }
+    return Ok(());
+}
+
+// As attach, for parts and part numbers in the same row
+fn attach_within(row: &mut WindowRow, sums: &mut Sums, on_event: &mut impl FnMut(StreamEvent)) -> Result<(), String> {
+    for part_id in 0..row.parts.len() {
+        for part_number_id in adjacent_part_number_ids(&row.part_number_index, &row.parts[part_id]) {
+            row.adjacent_values[part_id].push(row.part_numbers[part_number_id].value);
+            mark_attached(row, part_number_id, sums, on_event)?;
+        }
+    }
+    return Ok(());
+}
+
+fn mark_attached(
+    row: &mut WindowRow,
+    part_number_id: usize,
+    sums: &mut Sums,
+    on_event: &mut impl FnMut(StreamEvent),
+) -> Result<(), String> {
+    if row.is_attached[part_number_id] {
+        return Ok(());
+    }
+    row.is_attached[part_number_id] = true;
+
+    let part_number: &PartNumber = &row.part_numbers[part_number_id];
+    sums.part_number_sum = match sums.part_number_sum.checked_add(part_number.value) {
+        Some(sum) => sum,
+        None => {
+            return Err("Sum of part numbers overflowed".to_string());
+        },
+    };
+    on_event(StreamEvent::PartNumber(PartNumberNode::from(part_number)));
+    return Ok(());
+}
+
+// The part numbers in a row that are within one column of the part, in reading order
+pub(crate) fn adjacent_part_number_ids(part_number_index: &[Option<usize>], part: &EnginePart) -> Vec<usize> {
+    let mut part_number_ids: Vec<usize> = Vec::new();
+    let x_end: usize = (part.x_loc + part.width + 1).min(part_number_index.len());
+    for part_number_id in part_number_index.get(part.x_loc.saturating_sub(1)..x_end).unwrap_or(&[]).iter().flatten() {
+        if part_number_ids.last() != Some(part_number_id) {
+            part_number_ids.push(*part_number_id);
+        }
+    }
+    return part_number_ids;
+}
+
+pub(crate) fn finish_gears(row: &WindowRow, gear_rules: &GearRules, sums: &mut Sums, on_event: &mut impl FnMut(StreamEvent)) -> Result<(), String> {
+    for (part, values) in row.parts.iter().zip(&row.adjacent_values) {
+        if !gear_rules.symbols.contains(&part.value) || !gear_rules.neighbour_count.allows(values.len()) {
+            continue;
+        }
+
+        let gear_ratio: i64 = match gear_rules.aggregation.apply(values) {
+            Some(gear_ratio) => gear_ratio,
+            None => {
+                return Err(format!("Gear ratio overflowed for gear at ({}, {})", part.x_loc, part.y_loc));
+            },
+        };
+        sums.gear_ratio_sum = match sums.gear_ratio_sum.checked_add(gear_ratio) {
+            Some(sum) => sum,
+            None => {
+                return Err("Sum of gear ratios overflowed".to_string());
+            },
+        };
+        on_event(StreamEvent::Gear(PartNode::from(part), gear_ratio));
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::generator;
+    use crate::sum_gear_ratios;
+    use crate::sum_part_numbers;
+    use crate::Adjacency;
+    use crate::Aggregation;
+    use crate::NeighbourCount;
+    use generator::GeneratorOptions;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn solve(engine_schematic: &str, column_model: &ColumnModel, number_format: &NumberFormat, gear_rules: &GearRules) -> (Sums, Vec<StreamEvent>) {
+        let mut events: Vec<StreamEvent> = Vec::new();
+        let sums: Sums = solve_streaming(
+            &mut Cursor::new(engine_schematic),
+            column_model,
+            number_format,
+            gear_rules,
+            &mut |event| events.push(event),
+        )
+        .unwrap();
+        return (sums, events);
+    }
+
+    #[test]
+    fn test_solve_streaming() {
+        let contents: String = fs::read_to_string("res/test_input.txt").unwrap();
+        let (sums, events) = solve(&contents, &ColumnModel::Char, &NumberFormat::default(), &GearRules::default());
+        assert_eq!(sums, Sums { part_number_sum: 4361, gear_ratio_sum: 467835 });
+
+        // 467 is attached as soon as the '*' below it is read, before its gear is finished
+        assert_eq!(events[0], StreamEvent::PartNumber(PartNumberNode { value: 467, x_start_loc: 0, x_end_loc: 3, y_loc: 0 }));
+        assert_eq!(events[1], StreamEvent::PartNumber(PartNumberNode { value: 35, x_start_loc: 2, x_end_loc: 4, y_loc: 2 }));
+        assert_eq!(events[2], StreamEvent::Gear(PartNode { value: '*', x_loc: 3, y_loc: 1 }, 16345));
+    }
+
+    #[test]
+    fn test_matches_whole_schematic() {
+        let gear_rules: [GearRules; 2] = [
+            GearRules::default(),
+            GearRules {
+                symbols: vec!['*', '#'],
+                neighbour_count: NeighbourCount::AtLeast(1),
+                aggregation: Aggregation::Custom(|values| values.iter().enumerate().map(|(i, value)| (i as i64 + 1) * value).sum()),
+            },
+        ];
+        let number_formats: [NumberFormat; 2] = [
+            NumberFormat::default(),
+            NumberFormat { signed: true, hexadecimal: false, underscores: true },
+        ];
+
+        for seed in 0..20 {
+            let options: GeneratorOptions = GeneratorOptions {
+                width: 25,
+                height: 25,
+                number_density: 0.3,
+                symbol_density: 0.3,
+                symbols: "#-_+".chars().collect(),
+                gear_frequency: 0.5,
+            };
+            let mut engine_schematic: String = generator::generate_schematic(seed, &options).unwrap().engine_schematic;
+            // Ragged rows and no trailing newline
+            if seed % 2 == 0 {
+                engine_schematic = engine_schematic.lines().map(|line| &line[..(line.len() + seed as usize) % 26]).collect::<Vec<&str>>().join("\n");
+            }
+
+            for (gear_rules, number_format) in gear_rules.iter().zip(&number_formats) {
+                let (sums, _) = solve(&engine_schematic, &ColumnModel::Char, number_format, gear_rules);
+                assert_eq!(
+                    sums.part_number_sum,
+                    sum_part_numbers(&engine_schematic, &ColumnModel::Char, number_format, &Adjacency::default()).unwrap()
+                );
+                assert_eq!(
+                    sums.gear_ratio_sum,
+                    sum_gear_ratios(&engine_schematic, &ColumnModel::Char, number_format, gear_rules, &Adjacency::default()).unwrap()
+                );
+            }
+        }
+
+        // A wide part reaches the number under its second column
+        let engine_schematic: &str = "\u{4e2d}..\n..5\n";
+        let (sums, _) = solve(engine_schematic, &ColumnModel::DisplayWidth, &NumberFormat::default(), &GearRules::default());
+        assert_eq!(sums.part_number_sum, 5);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use crate::columns::Column;
+use crate::is_digit;
+use crate::ColumnModel;
+
+// Which characters a schematic may contain besides digits
+pub struct CharacterSet {
+    // Characters that stand for an empty cell. They're rewritten to '.' when validating, which is what the rest of the
+    // engine treats as empty.
+    pub blanks: Vec<char>,
+    // Characters allowed as parts, or None to allow any character other than whitespace and control characters
+    pub symbols: Option<Vec<char>>,
+}
+
+// The puzzle's own format: '.' for empty cells, and any visible character as a part
+impl Default for CharacterSet {
+    fn default() -> CharacterSet {
+        return CharacterSet {
+            blanks: vec!['.'],
+            symbols: None,
+        };
+    }
+}
+
+impl CharacterSet {
+    fn allows_symbol(&self, character: char) -> bool {
+        return match &self.symbols {
+            Some(symbols) => symbols.contains(&character),
+            None => !character.is_whitespace() && !character.is_control() && character != '.',
+        };
+    }
+}
+
+// A single problem found in a schematic, at a cell or, if x is None, for a whole row
+#[derive(Debug, PartialEq, Eq)]
+pub struct Violation {
+    x: Option<usize>,
+    y: usize,
+    message: String,
+}
+
+impl Violation {
+    pub fn x(&self) -> Option<usize> {
+        return self.x;
+    }
+
+    pub fn y(&self) -> usize {
+        return self.y;
+    }
+
+    pub fn message(&self) -> &String {
+        return &self.message;
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.x {
+            Some(x) => write!(f, "({}, {}): {}", x, self.y, self.message),
+            None => write!(f, "row {}: {}", self.y, self.message),
+        };
+    }
+}
+
+// Checks the schematic is rectangular and only uses digits, blanks and allowed symbols, reporting every violation
+// at its column in the column model. A valid schematic is returned with each column of a blank rewritten to '.',
+// ready for the rest of the engine.
+pub fn validate_schematic(
+    engine_schematic: &String,
+    column_model: &ColumnModel,
+    character_set: &CharacterSet,
+) -> Result<String, Vec<Violation>> {
+    let mut rows: Vec<&str> = engine_schematic.split("\n").collect();
+    if rows.last() == Some(&"") {
+        rows.pop();
+    }
+
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut normalised: String = String::new();
+    let rows: Vec<Vec<Column>> = rows.iter().map(|row| column_model.lay_out_row(row)).collect();
+    let expected_width: usize = rows.first().map_or(0, |row| row.len());
+
+    for (y, row) in rows.iter().enumerate() {
+        let width: usize = row.len();
+        if width != expected_width {
+            violations.push(Violation {
+                x: None,
+                y,
+                message: format!("Row has {} cells, but the first row has {}", width, expected_width),
+            });
+        }
+
+        let mut is_blank: bool = false;
+        for (x, column) in row.iter().enumerate() {
+            let character: char = column.character;
+            // The extra columns of a wide glyph are checked along with the glyph
+            if column.glyph.is_empty() {
+                if is_blank {
+                    normalised.push('.');
+                }
+            } else if character_set.blanks.contains(&character) {
+                is_blank = true;
+                normalised.push('.');
+            } else if is_digit(character) || character_set.allows_symbol(character) {
+                is_blank = false;
+                normalised += &column.glyph;
+            } else {
+                is_blank = false;
+                violations.push(Violation {
+                    x: Some(x),
+                    y,
+                    message: format!("Unexpected character {:?}", character),
+                });
+            }
+        }
+        normalised.push('\n');
+    }
+
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+    return Ok(normalised);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_validate_schematic() {
+        let engine_schematic: String = "467..114\r\n...*\t...\n..35 .6\n".to_string();
+        let violations: Vec<String> = validate_schematic(&engine_schematic, &ColumnModel::Char, &CharacterSet::default())
+            .unwrap_err()
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                "(8, 0): Unexpected character '\\r'",
+                "row 1: Row has 8 cells, but the first row has 9",
+                "(4, 1): Unexpected character '\\t'",
+                "row 2: Row has 7 cells, but the first row has 9",
+                "(4, 2): Unexpected character ' '",
+            ]
+        );
+
+        let character_set: CharacterSet = CharacterSet {
+            blanks: vec![' ', '_'],
+            symbols: Some(vec!['*', '#']),
+        };
+        assert_eq!(validate_schematic(&"1 *\n_#2\n".to_string(), &ColumnModel::Char, &character_set), Ok("1.*\n.#2\n".to_string()));
+        let columns: Vec<Option<usize>> = validate_schematic(&"1.*\n+#2\n".to_string(), &ColumnModel::Char, &character_set)
+            .unwrap_err()
+            .iter()
+            .map(|violation| violation.x())
+            .collect();
+        assert_eq!(columns, vec![Some(1), Some(0)]);
+
+        // A full-width space is two columns wide, so it's rewritten as two blanks
+        let character_set: CharacterSet = CharacterSet {
+            blanks: vec!['.', '\u{3000}'],
+            symbols: None,
+        };
+        let engine_schematic: String = "1\u{3000}*\n1..*\n".to_string();
+        assert_eq!(validate_schematic(&engine_schematic, &ColumnModel::DisplayWidth, &character_set), Ok("1..*\n1..*\n".to_string()));
+        assert!(validate_schematic(&engine_schematic, &ColumnModel::Char, &character_set).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::fmt;
+use std::ops::Index;
+use std::ops::IndexMut;
+
+// A rectangular 2-D grid of cells, addressed by (x, y) with (0, 0) in the top left corner
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    // Row-major, so the cell at (x, y) is cells[y * width + x]
+    cells: Vec<T>,
+}
+
+// Which cells count as neighbours: orthogonal only, or including diagonals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+const FOUR_CONNECTED_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const EIGHT_CONNECTED_OFFSETS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+impl Grid<char> {
+    // Parses one row per line, with every row required to have the same number of characters.
+    // A newline at the end of the text doesn't start an extra row.
+    pub fn parse(text: &str) -> Result<Grid<char>, String> {
+        let rows: Vec<Vec<char>> = text_rows(text).iter().map(|line| line.chars().collect()).collect();
+        return Grid::from_rows(rows);
+    }
+
+    // As parse, but rows shorter than the longest row are padded at the end with fill instead of being rejected
+    pub fn parse_padded(text: &str, fill: char) -> Grid<char> {
+        let mut rows: Vec<Vec<char>> = text_rows(text).iter().map(|line| line.chars().collect()).collect();
+        let width: usize = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, fill);
+        }
+        // All rows now have the same length
+        return Grid::from_rows(rows).unwrap();
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let height: usize = rows.len();
+        let width: usize = rows.first().map_or(0, |row| row.len());
+
+        let mut cells: Vec<T> = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(format!("Row {} has {} cells, expected {}", y, row.len(), width));
+            }
+            cells.extend(row);
+        }
+
+        return Ok(Grid {
+            width,
+            height,
+            cells,
+        });
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Grid<T> {
+        let mut cells: Vec<T> = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+
+        return Grid {
+            width,
+            height,
+            cells,
+        };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        return x < self.width && y < self.height;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        return Some(&self.cells[y * self.width + x]);
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        return Some(&mut self.cells[y * self.width + x]);
+    }
+
+    // Returns the previous value, or None if (x, y) is outside the grid and nothing was set
+    pub fn set(&mut self, x: usize, y: usize, value: T) -> Option<T> {
+        return self.get_mut(x, y).map(|cell| std::mem::replace(cell, value));
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y >= self.height {
+            return None;
+        }
+        return Some(&self.cells[y * self.width..(y + 1) * self.width]);
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        return (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width]);
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let height: usize = if x < self.width { self.height } else { 0 };
+        return (0..height).map(move |y| &self.cells[y * self.width + x]);
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        return (0..self.width).map(move |x| self.column(x));
+    }
+
+    // Every cell in row-major order, with its coordinates
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        return self.cells.iter().enumerate().map(move |(i, cell)| ((i % self.width, i / self.width), cell));
+    }
+
+    // The in-bounds neighbours of (x, y), not including (x, y) itself
+    pub fn neighbours(&self, x: usize, y: usize, connectivity: Connectivity) -> impl Iterator<Item = (usize, usize)> {
+        let offsets: &'static [(isize, isize)] = match connectivity {
+            Connectivity::Four => &FOUR_CONNECTED_OFFSETS,
+            Connectivity::Eight => &EIGHT_CONNECTED_OFFSETS,
+        };
+        let (width, height) = (self.width, self.height);
+        return offsets.iter().filter_map(move |(dx, dy)| {
+            let neighbour_x: usize = x.checked_add_signed(*dx)?;
+            let neighbour_y: usize = y.checked_add_signed(*dy)?;
+            if neighbour_x < width && neighbour_y < height {
+                return Some((neighbour_x, neighbour_y));
+            }
+            return None;
+        });
+    }
+
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        return self.neighbours(x, y, Connectivity::Four);
+    }
+
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        return self.neighbours(x, y, Connectivity::Eight);
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        return Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        };
+    }
+
+    // Every cell connected to start through cells matching the predicate, in the order they were reached.
+    // Empty if start itself doesn't match or is outside the grid.
+    pub fn flood_fill(
+        &self,
+        start: (usize, usize),
+        connectivity: Connectivity,
+        predicate: impl Fn(&T) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut region: Vec<(usize, usize)> = Vec::new();
+        match self.get(start.0, start.1) {
+            Some(cell) if predicate(cell) => {},
+            _ => return region,
+        };
+
+        let mut visited: Vec<bool> = vec![false; self.cells.len()];
+        visited[start.1 * self.width + start.0] = true;
+        let mut to_visit: Vec<(usize, usize)> = vec![start];
+
+        while let Some((x, y)) = to_visit.pop() {
+            region.push((x, y));
+            for (neighbour_x, neighbour_y) in self.neighbours(x, y, connectivity) {
+                let i: usize = neighbour_y * self.width + neighbour_x;
+                if !visited[i] && predicate(&self.cells[i]) {
+                    visited[i] = true;
+                    to_visit.push((neighbour_x, neighbour_y));
+                }
+            }
+        }
+
+        return region;
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        return Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        };
+    }
+
+    // Swaps rows and columns, so the cell at (x, y) moves to (y, x)
+    pub fn transpose(&self) -> Grid<T> {
+        return Grid::from_fn(self.height, self.width, |x, y| self.cells[x * self.width + y].clone());
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        return Grid::from_fn(self.height, self.width, |x, y| self.cells[(self.height - 1 - x) * self.width + y].clone());
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Grid<T> {
+        return Grid::from_fn(self.height, self.width, |x, y| self.cells[x * self.width + (self.width - 1 - y)].clone());
+    }
+
+    // Mirrors left to right
+    pub fn flip_horizontal(&self) -> Grid<T> {
+        return Grid::from_fn(self.width, self.height, |x, y| self.cells[y * self.width + (self.width - 1 - x)].clone());
+    }
+
+    // Mirrors top to bottom
+    pub fn flip_vertical(&self) -> Grid<T> {
+        return Grid::from_fn(self.width, self.height, |x, y| self.cells[(self.height - 1 - y) * self.width + x].clone());
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    // Panics if (x, y) is outside the grid; use get for a bounds-checked lookup
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(self.in_bounds(x, y), "({}, {}) is outside the {}x{} grid", x, y, self.width, self.height);
+        return &self.cells[y * self.width + x];
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(self.in_bounds(x, y), "({}, {}) is outside the {}x{} grid", x, y, self.width, self.height);
+        return &mut self.cells[y * self.width + x];
+    }
+}
+
+// Prints one line per row with the cells side by side, so a Grid<char> prints back out as the text it was parsed from
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
+
+fn text_rows(text: &str) -> Vec<&str> {
+    let mut rows: Vec<&str> = text.split("\n").collect();
+    if rows.last() == Some(&"") {
+        rows.pop();
+    }
+    return rows;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const TEST_GRID: &str = "abc\ndef\n";
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid: Grid<char> = Grid::parse(TEST_GRID).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.to_string(), TEST_GRID);
+
+        assert!(Grid::parse("abc\nde").is_err());
+        assert_eq!(Grid::parse_padded("abc\nde", '.').to_string(), "abc\nde.\n");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid: Grid<char> = Grid::parse(TEST_GRID).unwrap();
+        assert_eq!(grid.neighbours4(0, 0).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours8(1, 0).count(), 5);
+        assert_eq!(grid.neighbours8(1, 1).count(), 5);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid: Grid<char> = Grid::parse(TEST_GRID).unwrap();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc\n");
+        assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid: Grid<char> = Grid::parse("#..\n.#.\n..#\n").unwrap();
+        assert_eq!(grid.flood_fill((0, 0), Connectivity::Four, |cell| *cell == '#').len(), 1);
+        assert_eq!(grid.flood_fill((0, 0), Connectivity::Eight, |cell| *cell == '#').len(), 3);
+        assert_eq!(grid.flood_fill((1, 0), Connectivity::Four, |cell| *cell == '.').len(), 3);
+        assert!(grid.flood_fill((0, 0), Connectivity::Four, |cell| *cell == '.').is_empty());
+    }
+}